@@ -0,0 +1,43 @@
+use crate::node::dedupe;
+use anyhow::Result;
+use indicatif::HumanBytes;
+
+#[derive(clap::Args)]
+pub struct Dedupe {
+    /// Report the space that would be saved without linking any files
+    #[arg(long)]
+    dry_run: bool,
+}
+
+impl super::Command for Dedupe {
+    fn run(self) -> Result<()> {
+        let report = dedupe(self.dry_run)?;
+
+        if report.cross_device > 0 {
+            eprintln!(
+                "{}",
+                console::style(format!(
+                    "{} identical files are on different file systems and cannot be linked",
+                    report.cross_device
+                ))
+                .yellow()
+            );
+        }
+
+        let message = match self.dry_run {
+            true => format!(
+                "{} files can be hard-linked, {} would be saved",
+                report.linked,
+                HumanBytes(report.saved)
+            ),
+            false => format!(
+                "{} files hard-linked, {} saved",
+                report.linked,
+                HumanBytes(report.saved)
+            ),
+        };
+        eprintln!("{}", console::style(message).green());
+
+        Ok(())
+    }
+}
@@ -1,16 +1,58 @@
-use crate::{module::NodeVersionResolver, node::Node, utils::notice::Notice};
-use anyhow::Result;
+use crate::{
+    module::{NodeVersionResolver, Setting},
+    node::{Node, dedupe},
+    npm::{install_default_packages, reinstall_packages},
+    utils::notice::Notice,
+};
+use anyhow::{Result, bail};
+use indicatif::HumanBytes;
 
 #[derive(clap::Args)]
 pub struct Install {
     /// The version number of Node.js
     version: String,
+
+    /// Reinstall the global packages from the specified installed version
+    #[arg(long, value_name = "VERSION")]
+    reinstall_packages_from: Option<String>,
+
+    /// Skip installing the default packages into the new version
+    #[arg(long)]
+    skip_default_packages: bool,
 }
 
 impl super::Command for Install {
     fn run(self) -> Result<()> {
         let version = NodeVersionResolver::parse(&self.version)?;
-        Node::new(version).ensure_fetched()?;
+        let reinstall_from = self
+            .reinstall_packages_from
+            .as_deref()
+            .map(NodeVersionResolver::resolve)
+            .transpose()?;
+        if reinstall_from.as_deref() == Some(version.to_string().as_str()) {
+            bail!(
+                "Cannot reinstall the global packages of Node@v{} into itself",
+                version
+            );
+        }
+
+        let fetched = Node::new(version.clone()).ensure_fetched()?;
+        if fetched && !self.skip_default_packages {
+            install_default_packages(&version.to_string())?;
+        }
+
+        if let Some(from) = reinstall_from {
+            reinstall_packages(&from, &version.to_string())?;
+        }
+
+        if fetched && Setting::global()?.get_dedupe_on_install() {
+            let report = dedupe(false)?;
+            eprintln!(
+                "Hard-linked {} files shared with other versions, {} saved",
+                report.linked,
+                HumanBytes(report.saved)
+            );
+        }
 
         let _ = Notice::from_version().send();
 
@@ -1,8 +1,13 @@
 use anyhow::Result;
 
 mod current;
+mod dedupe;
 mod install;
 mod list;
+mod npm;
+mod packages;
+mod pin;
+mod prune;
 mod shim;
 mod uninstall;
 mod r#use;
@@ -13,6 +18,9 @@ pub enum Subcommand {
     /// Get the currently used version
     Current(current::Current),
 
+    /// Hard-link identical files across the installed versions of Node.js
+    Dedupe(dedupe::Dedupe),
+
     /// Install the specified version of Node.js
     Install(install::Install),
 
@@ -22,6 +30,18 @@ pub enum Subcommand {
     /// List the all installed versions of Node.js
     Ls(list::List),
 
+    /// Manage the versions of npm
+    Npm(npm::Npm),
+
+    /// Manage the global packages of the installed versions of Node.js
+    Packages(packages::Packages),
+
+    /// Manage the versions of Node.js global tools run with
+    Pin(pin::Pin),
+
+    /// Remove installed versions of Node.js that are no longer used
+    Prune(prune::Prune),
+
     /// Manage executable shims placed in '$NVMD_HOME/bin'.
     Shim(shim::Shim),
 
@@ -31,7 +51,7 @@ pub enum Subcommand {
     /// Use the installed version of Node.js (default is global)
     Use(r#use::Use),
 
-    /// Get the path to the executable to where Node.js was installed
+    /// Get the path to where Node.js was installed, or to the executable a shim runs
     Which(which::Which),
 }
 
@@ -39,8 +59,13 @@ impl Subcommand {
     pub fn run(self) -> Result<()> {
         match self {
             Subcommand::Current(current) => current.run(),
+            Subcommand::Dedupe(dedupe) => dedupe.run(),
             Subcommand::Install(install) => install.run(),
             Subcommand::List(list) | Subcommand::Ls(list) => list.run(),
+            Subcommand::Npm(npm) => npm.run(),
+            Subcommand::Packages(packages) => packages.run(),
+            Subcommand::Pin(pin) => pin.run(),
+            Subcommand::Prune(prune) => prune.run(),
             Subcommand::Shim(shim) => shim.run(),
             Subcommand::Uninstall(uninstall) => uninstall.run(),
             Subcommand::Use(r#use) => r#use.run(),
@@ -0,0 +1,93 @@
+use crate::{
+    module::{Context, NodeVersionResolver, Setting, VersionFile},
+    npm,
+    utils::help::node_strict_available,
+};
+use anyhow::{Result, bail};
+
+/// Manage the versions of npm.
+///
+/// npm can be upgraded in the global prefix of a version of Node.js,
+/// or pinned for a project without changing the version of Node.js it uses.
+#[derive(clap::Args)]
+pub struct Npm {
+    #[command(subcommand)]
+    command: NpmSubcommand,
+}
+
+#[derive(clap::Subcommand)]
+enum NpmSubcommand {
+    /// Use a release of npm with a version of Node.js, or for the project
+    Use(NpmUse),
+}
+
+#[derive(clap::Args)]
+struct NpmUse {
+    /// The version number of npm
+    version: String,
+
+    /// Use the release for the project, it is written into the version file
+    #[arg(short, long, conflicts_with = "node")]
+    project: bool,
+
+    /// The version number of Node.js to install the release into (default is the current one)
+    #[arg(long)]
+    node: Option<String>,
+}
+
+impl super::Command for Npm {
+    fn run(self) -> Result<()> {
+        match self.command {
+            NpmSubcommand::Use(r#use) => match r#use.project {
+                true => r#use.use_project(),
+                false => r#use.use_version(),
+            },
+        }
+    }
+}
+
+impl NpmUse {
+    fn use_version(self) -> Result<()> {
+        let context = match &self.node {
+            Some(node) => {
+                let version = NodeVersionResolver::resolve(node)?;
+                if !node_strict_available(&version)? {
+                    bail!("Node@v{} has not been installed", &version);
+                }
+                &Context::from_version(version)
+            }
+            None => Context::global()?,
+        };
+
+        let installed = npm::install_npm(context, &self.version)?;
+        eprintln!(
+            "{} Now using npm v{} with Node@v{}",
+            console::style("✔").green(),
+            installed,
+            context.get_version().unwrap_or_default()
+        );
+        Ok(())
+    }
+
+    fn use_project(self) -> Result<()> {
+        let version = self.version.trim_start_matches('v');
+        if semver::Version::parse(version).is_err() {
+            bail!(
+                "The version of npm for a project must be exact, e.g. 'nvmd npm use 10.8.2 --project'"
+            );
+        }
+
+        // the release is installed right away, so a wrong version is reported now
+        let context = Context::global()?;
+        npm::release_bin_dir(context, version)?;
+
+        let file_name = Setting::global()?.get_node_version_file();
+        let path = std::env::current_dir()?.join(&file_name);
+        let mut version_file = VersionFile::read(&path).unwrap_or_default();
+        version_file.set_npm(version);
+        version_file.write(&path)?;
+
+        eprintln!("Now using npm v{} for the project", version);
+        Ok(())
+    }
+}
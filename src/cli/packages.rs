@@ -0,0 +1,46 @@
+use crate::{module::NodeVersionResolver, npm::reinstall_packages};
+use anyhow::{Result, bail};
+
+/// Manage the global packages of the installed versions of Node.js
+#[derive(clap::Args)]
+pub struct Packages {
+    #[command(subcommand)]
+    command: PackagesSubcommand,
+}
+
+#[derive(clap::Subcommand)]
+enum PackagesSubcommand {
+    /// Reinstall the global packages of one version into another
+    Migrate(PackagesMigrate),
+}
+
+#[derive(clap::Args)]
+struct PackagesMigrate {
+    /// The version to reinstall the global packages from
+    #[arg(long)]
+    from: String,
+
+    /// The version to reinstall the global packages into
+    #[arg(long)]
+    to: String,
+}
+
+impl super::Command for Packages {
+    fn run(self) -> Result<()> {
+        match self.command {
+            PackagesSubcommand::Migrate(migrate) => {
+                let from = NodeVersionResolver::resolve(&migrate.from)?;
+                let to = NodeVersionResolver::resolve(&migrate.to)?;
+                if from == to {
+                    bail!(
+                        "Cannot migrate the global packages of Node@v{} into itself",
+                        from
+                    );
+                }
+
+                reinstall_packages(&from, &to)?;
+            }
+        };
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use crate::module::{Context, NodeVersionResolver, Packages, Pins};
+use anyhow::{Result, bail};
+
+/// Manage the versions of Node.js global tools run with.
+///
+/// A global tool missing from the active version runs with the version it is pinned to,
+/// or with the newest version providing it.
+#[derive(clap::Args)]
+pub struct Pin {
+    #[command(subcommand)]
+    command: PinSubcommand,
+}
+
+#[derive(clap::Subcommand)]
+enum PinSubcommand {
+    /// Pin a global tool to an installed version
+    Set(PinSet),
+    /// Remove the pinned version of a global tool
+    Unset(PinUnset),
+    /// List the global tools and the versions they run with
+    List,
+    /// List the global tools and the versions they run with (alias for 'list')
+    Ls,
+}
+
+#[derive(clap::Args)]
+struct PinSet {
+    /// tool name
+    tool: String,
+    /// The version number of Node.js
+    version: String,
+}
+
+#[derive(clap::Args)]
+struct PinUnset {
+    /// tool name
+    tool: String,
+}
+
+impl super::Command for Pin {
+    fn run(self) -> Result<()> {
+        match self.command {
+            PinSubcommand::Set(set) => {
+                let version = NodeVersionResolver::resolve(&set.version)?;
+                if Context::from_version(version.clone())
+                    .check_lib_path(set.tool.as_ref())
+                    .is_err()
+                {
+                    bail!("'{}' is not installed under Node@v{}", &set.tool, &version);
+                }
+
+                let mut pins = Pins::new()?;
+                pins.pin(&set.tool, &version);
+                pins.save()?;
+                eprintln!(
+                    "{} '{}' pinned to Node@v{}",
+                    console::style("✔").green(),
+                    &set.tool,
+                    &version
+                );
+            }
+            PinSubcommand::Unset(unset) => {
+                let mut pins = Pins::new()?;
+                if !pins.unpin(&unset.tool) {
+                    bail!("'{}' is not pinned", &unset.tool);
+                }
+                pins.save()?;
+                eprintln!("{} '{}' unpinned", console::style("✔").green(), &unset.tool);
+            }
+            PinSubcommand::List | PinSubcommand::Ls => {
+                let pins = Pins::new()?;
+                let packages = Packages::new()?;
+                for tool in packages.bins() {
+                    let versions = Pins::candidates(tool, &packages);
+                    if versions.is_empty() {
+                        continue;
+                    }
+
+                    let versions = versions
+                        .iter()
+                        .map(|v| format!("v{}", v))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    match pins.get(tool) {
+                        Some(pinned) => eprintln!("{} v{} (pinned) [{}]", tool, pinned, versions),
+                        None => eprintln!("{} [{}]", tool, versions),
+                    }
+                }
+            }
+        };
+        Ok(())
+    }
+}
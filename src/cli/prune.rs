@@ -0,0 +1,243 @@
+use super::uninstall::remove_orphaned_shims;
+use crate::{
+    module::{Context, Groups, NodeVersionResolver, Projects, Setting, VersionFile, nvmd_home},
+    node::{installed_at, reclaimable_size},
+    utils::{help::confirm, notice::Notice},
+};
+use anyhow::{Result, anyhow, bail};
+use fs_extra::{dir, file::read_to_string};
+use indicatif::HumanBytes;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+#[derive(clap::Args)]
+pub struct Prune {
+    /// Keep the latest installed version of each major release
+    #[arg(long)]
+    keep_latest_per_major: bool,
+
+    /// Only prune versions installed before this duration (e.g. 48h, 90d, 12w)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    older_than: Option<Duration>,
+
+    /// Keep the versions matching this spec (e.g. 20, 18.19, 16.20.2), can be repeated
+    #[arg(long, value_name = "SPEC")]
+    keep: Vec<String>,
+
+    /// Prune without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+}
+
+impl super::Command for Prune {
+    fn run(self) -> Result<()> {
+        let directory = Setting::global()?.get_directory()?;
+        let mut installed = NodeVersionResolver::installed()?;
+        installed.sort();
+
+        let referenced = referenced_versions()?;
+        let latest_per_major = latest_per_major(&installed);
+
+        let mut candidates = vec![];
+        for version in &installed {
+            if referenced.contains(&version.to_string()) {
+                continue;
+            }
+            if self.keep_latest_per_major && latest_per_major.contains(version) {
+                continue;
+            }
+            if self.is_kept(version)? {
+                continue;
+            }
+
+            let path = directory.join(version.to_string());
+            if let Some(older_than) = self.older_than
+                && installed_within(&path, older_than)
+            {
+                continue;
+            }
+
+            let size = reclaimable_size(&path);
+            candidates.push((version.to_string(), path, size));
+        }
+
+        if candidates.is_empty() {
+            eprintln!("No unused Node.js versions to prune");
+            return Ok(());
+        }
+
+        eprintln!("The following versions will be removed:");
+        for (version, _, size) in &candidates {
+            eprintln!("  v{} ({})", version, HumanBytes(*size));
+        }
+        let total = candidates.iter().map(|(_, _, size)| size).sum::<u64>();
+        eprintln!("{} will be reclaimed", HumanBytes(total));
+
+        if !self.yes && !confirm("Do you want to continue?")? {
+            bail!("Prune aborted");
+        }
+
+        for (version, path, _) in &candidates {
+            dir::remove(path)?;
+            remove_orphaned_shims(version)?;
+            eprintln!("Removed Node@v{}", version);
+        }
+        eprintln!(
+            "{}",
+            console::style(format!(
+                "Pruned {} versions, {} reclaimed",
+                candidates.len(),
+                HumanBytes(total)
+            ))
+            .green()
+        );
+
+        let _ = Notice::from_version().send();
+
+        Ok(())
+    }
+}
+
+impl Prune {
+    fn is_kept(&self, version: &semver::Version) -> Result<bool> {
+        for spec in &self.keep {
+            if NodeVersionResolver::matches(spec, version)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Collect the versions referenced by the default file, the current context,
+/// projects and groups, resolved to the installed versions
+fn referenced_versions() -> Result<HashSet<String>> {
+    let mut specs = vec![];
+
+    let default_path = nvmd_home()?.default_path();
+    if default_path.is_file() {
+        specs.push(read_to_string(&default_path)?);
+    }
+
+    if let Some(version) = Context::global().ok().and_then(|c| c.get_version()) {
+        specs.push(version);
+    }
+
+    let groups = Groups::new()?;
+    specs.extend(groups.data.iter().filter_map(|g| g.version.clone()));
+
+    let file_name = Setting::global()?.get_node_version_file();
+    for project in Projects::new()?.data {
+        let nvmdrc = Path::new(&project.path).join(&file_name);
+        if nvmdrc.is_file() {
+            specs.extend(VersionFile::read(&nvmdrc)?.node);
+        }
+
+        match project.version {
+            Some(name) if groups.exists(&name) => {
+                if let Some(version) = groups.find_by_name(&name).and_then(|g| g.version.clone()) {
+                    specs.push(version);
+                }
+            }
+            Some(version) => specs.push(version),
+            None => {}
+        }
+    }
+
+    Ok(specs
+        .iter()
+        .filter(|spec| !spec.trim().is_empty())
+        .filter_map(|spec| NodeVersionResolver::resolve(spec.trim()).ok())
+        .collect())
+}
+
+fn latest_per_major(versions: &[semver::Version]) -> HashSet<semver::Version> {
+    let mut latest: HashMap<u64, &semver::Version> = HashMap::new();
+    for version in versions {
+        latest
+            .entry(version.major)
+            .and_modify(|v| {
+                if version > *v {
+                    *v = version;
+                }
+            })
+            .or_insert(version);
+    }
+    latest.into_values().cloned().collect()
+}
+
+/// Versions whose install time is unknown are kept
+fn installed_within(path: &Path, duration: Duration) -> bool {
+    let Some(installed) = installed_at(path) else {
+        return true;
+    };
+    SystemTime::now()
+        .duration_since(installed)
+        .map_or(true, |elapsed| elapsed < duration)
+}
+
+/// Parse durations like '48h', '90d' or '12w'
+fn parse_duration(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    let error = || {
+        anyhow!(
+            "Invalid duration '{}', expected e.g. 48h, 90d or 12w",
+            input
+        )
+    };
+    let unit = input.chars().last().ok_or_else(error)?;
+    let value = input[..input.len() - unit.len_utf8()]
+        .parse::<u64>()
+        .map_err(|_| error())?;
+
+    let hours = match unit {
+        'h' => value,
+        'd' => value * 24,
+        'w' => value * 24 * 7,
+        _ => return Err(error()),
+    };
+    Ok(Duration::from_secs(hours * 60 * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{latest_per_major, parse_duration};
+    use std::time::Duration;
+
+    #[test]
+    fn parse_duration_with_units() {
+        assert_eq!(
+            parse_duration("48h").unwrap(),
+            Duration::from_secs(48 * 3600)
+        );
+        assert_eq!(
+            parse_duration("90d").unwrap(),
+            Duration::from_secs(90 * 86400)
+        );
+        assert_eq!(
+            parse_duration("2w").unwrap(),
+            Duration::from_secs(14 * 86400)
+        );
+        assert!(parse_duration("90").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3y").is_err());
+    }
+
+    #[test]
+    fn keep_latest_version_of_each_major() {
+        let versions = ["18.19.0", "18.20.3", "20.10.0", "20.11.1", "22.1.0"]
+            .into_iter()
+            .map(|version| semver::Version::parse(version).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut latest = latest_per_major(&versions)
+            .into_iter()
+            .map(|version| version.to_string())
+            .collect::<Vec<_>>();
+        latest.sort();
+        assert_eq!(latest, vec!["18.20.3", "20.11.1", "22.1.0"]);
+    }
+}
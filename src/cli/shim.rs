@@ -1,6 +1,10 @@
-use crate::{module::nvmd_home, utils::help};
+use crate::{
+    module::{Context, NodeVersionResolver, Packages, nvmd_home},
+    npm,
+    utils::help,
+};
 use anyhow::Result;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 /// Manage executable shims placed in '$NVMD_HOME/bin'.
 ///
@@ -18,10 +22,12 @@ enum ShimSubcommand {
     Add(ShimAdd),
     /// Remove a shim from $NVMD_HOME/bin
     Remove(ShimRemove),
+    /// Rebuild the shims and their registry from the installed versions
+    Sync(ShimSync),
     /// List all shims
-    List,
+    List(ShimList),
     /// List all shims (alias for 'list')
-    Ls,
+    Ls(ShimList),
 }
 
 #[derive(clap::Args)]
@@ -36,35 +42,144 @@ struct ShimRemove {
     name: String,
 }
 
+#[derive(clap::Args)]
+struct ShimList {
+    /// Show the packages and the versions of Node.js that provide each shim
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+#[derive(clap::Args)]
+struct ShimSync {
+    /// Show what would change without touching anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
 impl super::Command for Shim {
     fn run(self) -> Result<()> {
         match self.command {
             ShimSubcommand::Add(add) => {
                 help::link_package(&add.name)?;
                 eprintln!(
-                    "{} {}",
+                    "{} Shim '{}' created",
                     console::style("✔").green(),
-                    format!("Shim '{}' created", &add.name)
+                    &add.name
                 );
             }
             ShimSubcommand::Remove(remove) => {
                 help::unlink_package(&remove.name)?;
                 eprintln!(
-                    "{} {}",
+                    "{} Shim '{}' removed",
                     console::style("✔").green(),
-                    format!("Shim '{}' removed", &remove.name)
+                    &remove.name
                 );
             }
-            ShimSubcommand::List | ShimSubcommand::Ls => {
-                for shim in get_shims()? {
-                    eprintln!("{shim}");
-                }
-            }
+            ShimSubcommand::Sync(sync) => sync.run()?,
+            ShimSubcommand::List(list) | ShimSubcommand::Ls(list) => list.run()?,
         };
         Ok(())
     }
 }
 
+impl ShimList {
+    fn run(self) -> Result<()> {
+        let packages = match self.verbose {
+            true => Some(Packages::new()?),
+            false => None,
+        };
+
+        for shim in get_shims()? {
+            eprintln!("{shim}");
+            let Some(packages) = &packages else {
+                continue;
+            };
+
+            // group the Node versions by the package providing the shim
+            let mut owners = BTreeMap::<String, Vec<String>>::new();
+            for owner in packages.owners(&shim) {
+                let package = match &owner.record.version {
+                    Some(version) => format!("{}@{}", owner.name, version),
+                    None => owner.name.to_string(),
+                };
+                let version = match owner.node_version {
+                    Some(version) => format!("v{}", version),
+                    // installed into a global directory shared by the versions, e.g. by pnpm
+                    None => "all versions".to_string(),
+                };
+                owners.entry(package).or_default().push(version);
+            }
+
+            if owners.is_empty() && !help::is_core_shim(&shim) {
+                eprintln!("  not provided by any recorded package");
+            }
+            for (package, versions) in owners {
+                eprintln!("  {} ({})", package, versions.join(", "));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ShimSync {
+    fn run(self) -> Result<()> {
+        let mut packages = Packages::new()?;
+        packages.rebuild(npm::scan_global_bins()?);
+        let wanted = packages
+            .bins()
+            .into_iter()
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let existing = get_shims()?;
+
+        let missing = wanted.difference(&existing).collect::<Vec<_>>();
+        // 'nvmd' is never listed, and the core shims are not provided by global packages.
+        // The tools some version still has without a package (e.g. enabled by corepack) are kept too.
+        let versions = NodeVersionResolver::installed()?
+            .into_iter()
+            .map(|version| Context::from_version(version.to_string()))
+            .collect::<Vec<_>>();
+        let dangling = existing
+            .difference(&wanted)
+            .filter(|name| !help::is_core_shim(name))
+            .filter(|name| {
+                !versions
+                    .iter()
+                    .any(|context| context.check_lib_path(name.as_ref()).is_ok())
+            })
+            .collect::<Vec<_>>();
+
+        if self.dry_run {
+            for name in &missing {
+                eprintln!("Would create shim '{}'", name);
+            }
+            for name in &dangling {
+                eprintln!("Would remove shim '{}'", name);
+            }
+            return Ok(());
+        }
+
+        packages.save()?;
+
+        for name in &missing {
+            help::link_package(name)?;
+            eprintln!("Created shim '{}'", name);
+        }
+        for name in &dangling {
+            help::unlink_package(name)?;
+            eprintln!("Removed shim '{}'", name);
+        }
+
+        eprintln!(
+            "{} Shims synced: {} created, {} removed",
+            console::style("✔").green(),
+            missing.len(),
+            dangling.len()
+        );
+        Ok(())
+    }
+}
+
 fn get_shims() -> Result<BTreeSet<String>> {
     let mut shims = BTreeSet::new();
     let bin_dir = nvmd_home()?.bin_dir();
@@ -1,6 +1,9 @@
 use crate::{
-    module::{NodeVersionResolver, Setting},
-    utils::notice::Notice,
+    module::{NodeVersionResolver, Packages, Setting},
+    utils::{
+        help::{is_core_shim, unlink_package},
+        notice::Notice,
+    },
 };
 use anyhow::{Result, bail};
 use console::style;
@@ -17,7 +20,7 @@ impl super::Command for Uninstall {
         let version = NodeVersionResolver::parse(&self.version)?;
         let path = Setting::global()?
             .get_directory()?
-            .join(&version.to_string());
+            .join(version.to_string());
 
         if !path.exists() {
             bail!("Node@v{} has not been installed", &version);
@@ -34,8 +37,25 @@ impl super::Command for Uninstall {
             .green()
         );
 
+        remove_orphaned_shims(&version.to_string())?;
+
         let _ = Notice::from_version().send();
 
         Ok(())
     }
 }
+
+/// Drop the uninstalled version from 'packages.json' and remove the shims
+/// of global packages that are no longer provided by any installed version
+pub(super) fn remove_orphaned_shims(version: &str) -> Result<()> {
+    let mut packages = Packages::new()?;
+    let orphaned = packages.record_version_removed(version);
+    packages.save()?;
+
+    for name in orphaned.iter().filter(|name| !is_core_shim(name)) {
+        unlink_package(name)?;
+        eprintln!("Removed shim '{}'", name);
+    }
+
+    Ok(())
+}
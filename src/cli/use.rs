@@ -1,5 +1,5 @@
 use crate::{
-    module::{Groups, NodeVersionResolver, Projects, Setting, nvmd_home},
+    module::{Groups, NodeVersionResolver, Projects, Setting, VersionFile, nvmd_home},
     utils::{help::node_strict_available, notice::Notice},
 };
 use anyhow::{Result, anyhow, bail};
@@ -81,8 +81,11 @@ impl Use {
         }
 
         let file_name = Setting::global()?.get_node_version_file();
+        // the versions of the other tools in the file are kept
         let nvmdrc = project_path.join(&file_name);
-        write_all(nvmdrc, &version)?;
+        let mut version_file = VersionFile::read(&nvmdrc).unwrap_or_default();
+        version_file.node = Some(version.clone());
+        version_file.write(&nvmdrc)?;
 
         if is_group {
             eprintln!("Now using node v{} ({})", &version, &self.version);
@@ -1,26 +1,123 @@
-use crate::module::{NodeVersionResolver, Setting};
+use crate::module::{
+    Context, NodeVersionResolver, Pins, Setting, VersionSource, nvmd_home, package_manager_of,
+    project_bin,
+};
 use anyhow::{Result, bail};
+use std::env;
 
+/// Print the Node.js installation directory of a version, or the executable a shim runs.
+///
+/// For a tool, the version is resolved from the current directory the same way the shim does.
 #[derive(clap::Args)]
 pub struct Which {
-    /// The version number of Node.js
+    /// The version number of Node.js, or the name of a tool (e.g. node, npm, tsc)
     version: String,
 }
 
 impl super::Command for Which {
     fn run(self) -> Result<()> {
-        let version = NodeVersionResolver::resolve(&self.version)?;
-        let mut path = Setting::global()?.get_directory()?.join(&version);
-        if cfg!(unix) {
-            path.push("bin");
+        if is_version(&self.version) {
+            return which_version(&self.version);
         }
 
-        if path.exists() {
-            eprintln!("{:?}", path);
-        } else {
-            bail!("Node@v{} has not been installed", &version);
+        which_tool(&self.version)
+    }
+}
+
+fn which_version(input: &str) -> Result<()> {
+    let version = NodeVersionResolver::resolve(input)?;
+    let mut path = Setting::global()?.get_directory()?.join(&version);
+    if cfg!(unix) {
+        path.push("bin");
+    }
+
+    if path.exists() {
+        eprintln!("{:?}", path);
+    } else {
+        bail!("Node@v{} has not been installed", &version);
+    }
+
+    Ok(())
+}
+
+fn which_tool(tool: &str) -> Result<()> {
+    let global_context = Context::global()?;
+    // the same resolution as 'core::binary' for the tools that are not part of Node.js,
+    // the ones the project depends on first
+    let is_binary = !is_node_tool(tool) && package_manager_of(tool).is_none();
+    let local_bin = match is_binary && Setting::global()?.get_prefer_local_bins() {
+        true => project_bin(&env::current_dir()?, tool),
+        false => None,
+    };
+    let pinned_context = match is_binary && local_bin.is_none() {
+        true => Pins::resolve(tool, global_context)?.map(Context::from_version),
+        false => None,
+    };
+    let context = pinned_context.as_ref().unwrap_or(global_context);
+
+    let Some(version) = context.get_version() else {
+        bail!(
+            "The default Node version is not set, you can set it by executing \"nvmd use {{version}}\""
+        );
+    };
+    let Some(path) = local_bin.or_else(|| context.lib_path(tool.as_ref()).ok()) else {
+        match tool {
+            "yarn" | "yarnpkg" | "pnpm" | "pnpx" => bail!(
+                "'{}' is not installed under Node@v{}, you can enable it by executing \"corepack enable\"",
+                tool,
+                &version
+            ),
+            _ => bail!("'{}' is not installed under Node@v{}", tool, &version),
+        }
+    };
+
+    let reason = match (&pinned_context, context.source()) {
+        (Some(_), _) if Pins::new()?.get(tool) == Some(&version) => {
+            format!("'{}' is pinned to it", tool)
+        }
+        (Some(_), _) => format!("the newest version that provides '{}'", tool),
+        (None, Some(VersionSource::Env)) => {
+            "the NVMD_NODE_VERSION environment variable".to_string()
+        }
+        (None, Some(VersionSource::Parent(file))) => {
+            format!("the version file {:?}, resolved by the parent shim", file)
         }
+        (None, Some(VersionSource::File(file))) if *file == nvmd_home()?.default_path() => {
+            format!("the default version {:?}", file)
+        }
+        (None, Some(VersionSource::File(file))) => format!("the version file {:?}", file),
+        (None, _) => "specified".to_string(),
+    };
+
+    println!("{}", path.display());
+    eprintln!("Node@v{} ({})", &version, reason);
+    Ok(())
+}
+
+/// Whether the input is a version number rather than the name of a tool, e.g. 'v20', '20.11.1'
+fn is_version(input: &str) -> bool {
+    let input = input.trim();
+    let input = input.strip_prefix('v').unwrap_or(input);
+    !input.is_empty() && input.split('.').all(|part| part.parse::<u64>().is_ok())
+}
+
+/// The tools shipped with Node.js, which always run with the resolved version
+fn is_node_tool(tool: &str) -> bool {
+    matches!(tool, "node" | "npm" | "npx" | "corepack")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_version;
 
-        Ok(())
+    #[test]
+    fn tell_versions_from_tools() {
+        assert!(is_version("20"));
+        assert!(is_version("v20.11"));
+        assert!(is_version("20.11.1"));
+        assert!(!is_version("node"));
+        assert!(!is_version("tsc"));
+        assert!(!is_version("v8-profiler"));
+        assert!(!is_version("v"));
     }
 }
@@ -1,22 +1,44 @@
-use crate::module::Context;
-use crate::signal::pass_control_to_shim;
+use crate::module::{Context, Pins, Setting, project_bin};
 use crate::utils::command;
 use anyhow::Result;
+use std::env;
 use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
 use std::process::ExitStatus;
 
 pub(super) fn command(exe: &OsStr, args: &[OsString]) -> Result<ExitStatus> {
-    let global_context = Context::global()?;
-    // check if the lib is installed under current Nodejs version, if not, return an error
-    global_context.check_lib_path(exe)?;
+    // a tool the project depends on runs from its 'node_modules/.bin' with the version of the project
+    if let Some(path) = local_bin(exe)? {
+        let mut command = command::create_command(path);
+        command.args(args);
+        Context::global()?.apply_env(&mut command)?;
 
-    let path = global_context.env_path()?;
-    let mut command = command::create_command(exe);
+        return super::exec(&mut command);
+    }
+
+    let pinned_context = pinned_context(exe)?;
+    let context = pinned_context.as_ref().unwrap_or(Context::global()?);
+    // check if the lib is installed under the Nodejs version, if not, return an error
+    let path = context.lib_path(exe)?;
+
+    let mut command = command::create_command(path);
     command.args(args);
-    command.env("PATH", path);
+    context.apply_env(&mut command)?;
 
-    pass_control_to_shim();
+    super::exec(&mut command)
+}
+
+/// The tool in the nearest 'node_modules/.bin' of the current directory,
+/// unless the 'prefer_local_bins' setting is off
+fn local_bin(exe: &OsStr) -> Result<Option<PathBuf>> {
+    if !Setting::global()?.get_prefer_local_bins() {
+        return Ok(None);
+    }
+    Ok(project_bin(&env::current_dir()?, exe))
+}
 
-    let status = command.status()?;
-    Ok(status)
+/// The context of the version the tool is pinned to,
+/// if it is not installed under current Nodejs version
+pub(super) fn pinned_context(exe: &OsStr) -> Result<Option<Context>> {
+    Ok(Pins::resolve(&exe.to_string_lossy(), Context::global()?)?.map(Context::from_version))
 }
@@ -1,13 +1,16 @@
-use crate::module::{Context, Packages};
-use crate::signal::pass_control_to_shim;
+use super::supervise;
+use crate::module::{COREPACK_MANAGERS, Context, PackageJson, PackageManager, Packages, nvmd_home};
+use crate::npm;
+use crate::utils::args::{flag_value, has_global, split_args};
 use crate::utils::command;
-use crate::utils::help::{link_package, unlink_package};
 use anyhow::Result;
 use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
 use std::process::ExitStatus;
 
 const ENABLE: &str = "enable";
 const DISABLE: &str = "disable";
+const INSTALL: &str = "install";
 const INSTALL_DIRECTORY: &str = "--install-directory";
 
 // corepack enable --install-directory /path/to/folder
@@ -21,78 +24,143 @@ const INSTALL_DIRECTORY: &str = "--install-directory";
 // directory via the `--install-directory` flag.
 
 pub(super) fn command(exe: &OsStr, args: &[OsString]) -> Result<ExitStatus> {
-    let path = Context::global()?.env_path()?;
+    let context = Context::global()?;
+    ensure_standalone(context, exe)?;
+    let path = context.lib_path(exe)?;
 
-    let mut command = command::create_command(exe);
+    let mut command = command::create_command(&path);
     command.args(args);
-    command.env("PATH", path);
-
-    pass_control_to_shim();
-
-    let status = command.status()?;
+    context.apply_env(&mut command)?;
+    // the standalone corepack finds its install directory from PATH, it must not be '$NVMD_HOME/bin'
+    if !context.bundles_corepack()
+        && let Some(bin_dir) = path.parent()
+    {
+        command.env("PATH", context.env_path_with(bin_dir)?);
+    }
 
-    let install_directory = args.iter().any(|a| a == INSTALL_DIRECTORY);
-    if !install_directory {
-        if args.iter().any(|a| a == ENABLE) {
-            corepack_manager(args, true)?;
-        }
+    let status = supervise(&mut command)?;
 
-        if args.iter().any(|a| a == DISABLE) {
-            corepack_manager(args, false)?;
-        }
+    if status.success() {
+        corepack_manager(context, args)?;
     }
 
     Ok(status)
 }
 
-fn corepack_manager(args: &[OsString], enable: bool) -> Result<()> {
-    let mut shims: Vec<&str> = args
-        .iter()
-        .filter_map(|name| {
-            if is_supported_package(name) {
-                name.to_str()
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    if shims.is_empty() && !args.iter().any(|a| a == "npm") {
-        shims.extend(["yarn", "pnpm"]);
+/// Install the standalone corepack when the version of the context no longer bundles it,
+/// and nothing else provides the tool
+pub(super) fn ensure_standalone(context: &Context, tool: &OsStr) -> Result<()> {
+    if context.get_version().is_some()
+        && !context.bundles_corepack()
+        && context.lib_path(tool).is_err()
+    {
+        npm::install_corepack(context)?;
     }
+    Ok(())
+}
 
-    let packages = Packages::new()?;
-    for shim in shims {
-        if enable {
-            link_package(&shim)?;
-            for extra in package_extra_aliases(shim) {
-                link_package(extra)?;
-            }
-        } else if packages.can_be_removed(&shim) {
-            unlink_package(&shim)?;
-            for extra in package_extra_aliases(shim) {
-                unlink_package(extra)?;
+/// Run the exact release of the package manager the project declares, through corepack
+pub(super) fn run_declared(declared: &PackageManager, args: &[OsString]) -> Result<ExitStatus> {
+    let context = Context::global()?;
+    let corepack = OsStr::new("corepack");
+    ensure_standalone(context, corepack)?;
+    let path = context.lib_path(corepack)?;
+
+    let mut command = command::create_command(&path);
+    command
+        .arg(format!("{}@{}", declared.name, declared.version))
+        .args(args);
+    context.apply_env(&mut command)?;
+
+    super::exec(&mut command)
+}
+
+/// Record the package managers enabled or disabled under the version of the context,
+/// and update their shims
+fn corepack_manager(context: &Context, args: &[OsString]) -> Result<()> {
+    let (positionals, flags) = split_args(args, &[INSTALL_DIRECTORY]);
+    let Some((cmd, rest)) = positionals.split_first() else {
+        return Ok(());
+    };
+    let names = rest
+        .iter()
+        .filter_map(|arg| arg.to_str())
+        .collect::<Vec<_>>();
+
+    let version = context.get_version().unwrap_or_default();
+    let bin_dir = match flag_value(&flags, INSTALL_DIRECTORY) {
+        // corepack replaces the shims of nvmd when it installs its own into '$NVMD_HOME/bin'
+        Some(dir) if is_nvmd_bin_dir(dir) => return Ok(()),
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => None,
+    };
+
+    match cmd.to_str() {
+        Some(ENABLE) => {
+            let installed = managers(&names)
+                .map(|manager| {
+                    let package = PackageJson::corepack(manager);
+                    (package.name.clone().unwrap_or_default(), package)
+                })
+                .collect::<Vec<_>>();
+            npm::record_installed(&version, &installed, bin_dir.as_deref())
+        }
+        Some(DISABLE) => {
+            let removed = managers(&names)
+                .map(PackageJson::corepack)
+                .collect::<Vec<_>>();
+            npm::record_removed(&version, &removed)
+        }
+        // 'corepack install --global pnpm@9.1.0' only takes effect where pnpm is enabled
+        Some(INSTALL) if has_global(&flags) => {
+            let packages = Packages::new()?;
+            for spec in names {
+                let (manager, release) = spec.split_once('@').unwrap_or((spec, ""));
+                let package = PackageJson {
+                    version: (!release.is_empty()).then(|| release.to_string()),
+                    ..PackageJson::corepack(manager)
+                };
+                let name = package.name.clone().unwrap_or_default();
+                let Some(bin) = package.bin_names().into_iter().next() else {
+                    continue;
+                };
+
+                // keep the install directory it was enabled into
+                let recorded = packages
+                    .owners(&bin)
+                    .into_iter()
+                    .find(|owner| owner.node_version == Some(&version) && owner.name == name)
+                    .map(|owner| owner.record.bin_dir.clone());
+                let bin_dir = match recorded {
+                    Some(bin_dir) => bin_dir,
+                    None if context.check_lib_path(bin.as_ref()).is_ok() => None,
+                    None => continue,
+                };
+                npm::record_installed(&version, &[(name, package)], bin_dir.as_deref())?;
             }
+            Ok(())
         }
+        _ => Ok(()),
     }
-
-    Ok(())
 }
 
-fn package_extra_aliases(name: &str) -> &'static [&'static str] {
-    match name {
-        "yarn" => &["yarnpkg"],
-        "pnpm" => &["pnpx"],
-        _ => &[],
-    }
+/// The package managers named, corepack defaults to yarn and pnpm
+fn managers<'a>(names: &[&'a str]) -> impl Iterator<Item = &'a str> {
+    let names = match names.is_empty() {
+        true => vec!["yarn", "pnpm"],
+        false => names.to_vec(),
+    };
+    names
+        .into_iter()
+        .filter(|name| COREPACK_MANAGERS.contains(name))
 }
 
-fn is_supported_package<A>(arg: &A) -> bool
-where
-    A: AsRef<OsStr>,
-{
-    match arg.as_ref().to_str() {
-        Some(a) => a == "yarn" || a == "pnpm",
-        None => false,
+fn is_nvmd_bin_dir(dir: &str) -> bool {
+    let Ok(bin_dir) = nvmd_home().map(|home| home.bin_dir()) else {
+        return false;
+    };
+    match (PathBuf::from(dir).canonicalize(), bin_dir.canonicalize()) {
+        (Ok(dir), Ok(bin_dir)) => dir == bin_dir,
+        _ => false,
     }
 }
@@ -1,9 +1,11 @@
-use anyhow::{anyhow, Result};
+use crate::utils::command::{shim_chain, shim_depth, track_shim_depth};
+use crate::{pnpm, yarn};
+use anyhow::{Result, anyhow, bail};
 use std::{
     env::{self, ArgsOs},
     ffi::{OsStr, OsString},
     path::Path,
-    process::ExitStatus,
+    process::{Command, ExitStatus},
 };
 
 mod binary;
@@ -12,22 +14,84 @@ mod node;
 mod npm;
 mod npx;
 mod nvmd;
+mod package_manager;
+mod shared;
+
+pub use shared::SharedGlobalCommand;
+
+/// The maximum number of nested shims before it is considered a recursion
+const MAX_SHIM_DEPTH: usize = 20;
 
 pub fn execute() -> Result<ExitStatus> {
     let mut native_args = env::args_os();
     let exe = get_tool_name(&mut native_args)?;
     let args: Vec<_> = native_args.collect();
 
+    if exe != "nvmd" {
+        check_shim_depth(&exe)?;
+    }
+
+    if let Some(status) = package_manager::check(&exe, &args)? {
+        return Ok(status);
+    }
+
     match exe.to_str() {
         Some("nvmd") => nvmd::command(),
         Some("node") => node::command(&exe, &args),
         Some("npm") => npm::command(&exe, &args),
         Some("corepack") => corepack::command(&exe, &args),
         Some("npx") => npx::command(&exe, &args),
+        Some("pnpm") => shared::command::<pnpm::GlobalCommand>(&exe, &args),
+        Some("yarn") | Some("yarnpkg") => shared::command::<yarn::GlobalCommand>(&exe, &args),
         _ => binary::command(&exe, &args),
     }
 }
 
+/// Fail when the shims keep resolving back to themselves, e.g. when the bin directory
+/// of the version lacks the tool and PATH leads to '$NVMD_HOME/bin' again
+fn check_shim_depth(exe: &OsStr) -> Result<()> {
+    let depth = shim_depth();
+    if depth < MAX_SHIM_DEPTH {
+        return Ok(());
+    }
+
+    let chain = shim_chain();
+    let path = env::var_os("PATH").unwrap_or_default();
+    bail!(
+        "Shim recursion detected, {} nested shims have been run\n\
+         Resolution chain: {}\n\
+         PATH: {}\n\
+         Please ensure {:?} is installed under the current Node version and PATH is valid.",
+        depth,
+        chain.join(" -> "),
+        path.to_string_lossy(),
+        exe
+    );
+}
+
+/// Run the command of a shim as a child process, and wait for it to exit
+fn supervise(command: &mut Command) -> Result<ExitStatus> {
+    track_shim_depth(command);
+    crate::signal::supervise(command)
+}
+
+/// Run a command that needs no work after it exits.
+/// On Unix the current process is replaced by the command, so the shim does not sit
+/// between the command and the terminal, and signals are delivered to it directly.
+#[cfg(unix)]
+fn exec(command: &mut Command) -> Result<ExitStatus> {
+    use std::os::unix::process::CommandExt;
+
+    track_shim_depth(command);
+    // 'exec' only returns if the command could not be executed
+    Err(command.exec().into())
+}
+
+#[cfg(windows)]
+fn exec(command: &mut Command) -> Result<ExitStatus> {
+    supervise(command)
+}
+
 fn get_tool_name(args: &mut ArgsOs) -> Result<OsString> {
     args.next()
         .and_then(|arg0| Path::new(&arg0).file_name().map(tool_name_from_file_name))
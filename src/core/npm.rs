@@ -1,48 +1,47 @@
-use crate::module::Context;
-use crate::npm::{CommandArg, InterceptedCommand};
-use crate::signal::pass_control_to_shim;
+use super::supervise;
+use crate::module::{Context, executable};
+use crate::npm::{self, CommandArg};
 use crate::utils::command;
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use std::env;
 use std::ffi::{OsStr, OsString};
 use std::process::ExitStatus;
 
 pub(super) fn command(exe: &OsStr, args: &[OsString]) -> Result<ExitStatus> {
-    let path = Context::global()?.env_path()?;
+    let context = Context::global()?;
     let command_arg = CommandArg::from_npm(args);
 
+    // The project may use another release of npm than the one of the version
+    let release = match npm::project_npm(&env::current_dir()?)? {
+        Some(version) => npm::release_bin_dir(context, &version)?,
+        None => None,
+    };
+
     // Before command execution
-    match &command_arg {
-        CommandArg::Global(cmd) => {
-            cmd.before_executor()?;
-        }
-        CommandArg::Intercepted(InterceptedCommand::Unlink(unlink)) => {
-            unlink.before_executor()?;
-        }
-        _ => {}
+    let snapshot = match &command_arg {
+        CommandArg::Global(cmd) => Some(cmd.before_executor(context)),
+        CommandArg::Standard => None,
     };
 
-    let mut command = command::create_command(exe);
+    let mut command = match &release {
+        Some(bin_dir) => command::create_command(
+            executable(bin_dir, exe).ok_or_else(|| anyhow!("command not found: {:?}", exe))?,
+        ),
+        None => command::create_command(exe),
+    };
     command.args(args);
-    command.env("PATH", path);
-
-    pass_control_to_shim();
+    context.apply_env(&mut command)?;
+    if let Some(bin_dir) = &release {
+        command.env("PATH", context.env_path_with(bin_dir)?);
+    }
 
-    let status = command.status()?;
+    let status = supervise(&mut command)?;
 
-    // After the command is executed
-    if status.success() {
-        match &command_arg {
-            CommandArg::Global(cmd) => {
-                cmd.after_executor()?;
-            }
-            CommandArg::Intercepted(InterceptedCommand::Link(link)) => {
-                link.after_executor()?;
-            }
-            CommandArg::Intercepted(InterceptedCommand::Unlink(unlink)) => {
-                unlink.after_executor()?;
-            }
-            _ => {}
-        };
+    // After a successful command, record the global packages it changed
+    if let (CommandArg::Global(cmd), Some(snapshot)) = (&command_arg, snapshot)
+        && status.success()
+    {
+        cmd.after_executor(context, snapshot)?;
     }
 
     Ok(status)
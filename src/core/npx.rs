@@ -1,16 +1,13 @@
 use super::{ExitStatus, OsStr, OsString};
-use crate::{module::Context, signal::pass_control_to_shim, utils::command};
+use crate::{module::Context, utils::command};
 use anyhow::Result;
 
 pub(super) fn command(exe: &OsStr, args: &[OsString]) -> Result<ExitStatus> {
-    let path = Context::global()?.env_path()?;
+    let context = Context::global()?;
 
     let mut command = command::create_command(exe);
     command.args(args);
-    command.env("PATH", path);
+    context.apply_env(&mut command)?;
 
-    pass_control_to_shim();
-
-    let status = command.status()?;
-    Ok(status)
+    super::exec(&mut command)
 }
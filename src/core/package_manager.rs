@@ -0,0 +1,177 @@
+use crate::module::{PackageManager, Setting, package_manager_of};
+use crate::npm;
+use crate::utils::args::{has_global, split_args};
+use anyhow::{Result, bail};
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::process::ExitStatus;
+
+/// The commands that install or change the dependencies of a project, per package manager.
+/// The ones of npm are resolved by its own argument parser, with their aliases and abbreviations.
+const INSTALL_COMMANDS: [(&str, &[&str]); 2] = [
+    (
+        "pnpm",
+        &[
+            "install",
+            "i",
+            "install-test",
+            "it",
+            "add",
+            "remove",
+            "rm",
+            "uninstall",
+            "un",
+            "update",
+            "up",
+            "upgrade",
+            "dedupe",
+            "import",
+        ],
+    ),
+    (
+        "yarn",
+        &[
+            "install",
+            "add",
+            "remove",
+            "upgrade",
+            "up",
+            "upgrade-interactive",
+            "dedupe",
+            "import",
+        ],
+    ),
+];
+
+/// Check the package manager the project declares in 'packageManager' before running one.
+///
+/// Another package manager installing the dependencies is warned about, or refused with the
+/// 'package_manager_strict' setting.
+/// The declared pnpm or yarn runs through corepack, so the exact version declared runs,
+/// the npm shim runs the declared release of npm itself.
+/// Returns the exit status if the command was run here.
+pub(super) fn check(exe: &OsStr, args: &[OsString]) -> Result<Option<ExitStatus>> {
+    let Some(tool) = exe
+        .to_str()
+        .filter(|tool| package_manager_of(tool).is_some())
+    else {
+        return Ok(None);
+    };
+    // the global packages do not belong to the project, and corepack can be told not to check
+    if is_global(args) || env::var("COREPACK_ENABLE_STRICT").is_ok_and(|strict| strict == "0") {
+        return Ok(None);
+    }
+    let Some(declared) = PackageManager::find(&env::current_dir()?) else {
+        return Ok(None);
+    };
+
+    if !declared.provides(tool) {
+        // the scripts of the package manager may run others, only what is run directly is checked
+        if !installs_dependencies(tool, args) || is_run_by_package_manager() {
+            return Ok(None);
+        }
+
+        let message = format!(
+            "This project is configured to use {}@{} in {:?}, not {}",
+            declared.name, declared.version, declared.path, tool
+        );
+        if Setting::global()?.get_package_manager_strict() {
+            bail!(message);
+        }
+        eprintln!("{}", console::style(message).yellow());
+        return Ok(None);
+    }
+
+    match declared.name.as_str() {
+        "npm" => Ok(None),
+        _ => super::corepack::run_declared(&declared, args).map(Some),
+    }
+}
+
+/// Whether the command installs or changes the dependencies, e.g. 'npm ci' or a bare 'yarn'
+fn installs_dependencies(tool: &str, args: &[OsString]) -> bool {
+    if tool == "npm" {
+        return npm::changes_packages(args);
+    }
+    let Some((name, commands)) = INSTALL_COMMANDS
+        .iter()
+        .find(|(name, _)| package_manager_of(tool) == Some(name))
+    else {
+        return false;
+    };
+
+    let (positionals, flags) = split_args(args, &[]);
+    match positionals.first() {
+        Some(command) => commands.iter().any(|cmd| *command == *cmd),
+        // a bare 'yarn' installs the dependencies
+        None => {
+            *name == "yarn"
+                && !flags.iter().any(|flag| {
+                    ["-v", "--version", "-h", "--help"]
+                        .iter()
+                        .any(|f| flag == f)
+                })
+        }
+    }
+}
+
+/// Whether a package manager runs the command, e.g. in a script of the project,
+/// they all set these variables for the scripts they run
+fn is_run_by_package_manager() -> bool {
+    env::var_os("npm_lifecycle_event").is_some() || env::var_os("npm_config_user_agent").is_some()
+}
+
+/// Whether the command applies to the global packages, e.g. 'pnpm add -g' or 'yarn global add'
+fn is_global(args: &[OsString]) -> bool {
+    let (positionals, flags) = split_args(args, &[]);
+    has_global(&flags)
+        || flags.iter().any(|flag| *flag == "--location=global")
+        || positionals.first().is_some_and(|cmd| *cmd == "global")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::installs_dependencies;
+    use std::ffi::OsString;
+
+    fn installs(tool: &str, args: &str) -> bool {
+        let args = args
+            .split_whitespace()
+            .map(OsString::from)
+            .collect::<Vec<_>>();
+        installs_dependencies(tool, &args)
+    }
+
+    #[test]
+    fn check_only_the_commands_installing_dependencies() {
+        for (tool, args) in [
+            ("npm", "install"),
+            ("npm", "i --save-dev eslint"),
+            ("npm", "ci"),
+            ("npm", "uninstall eslint"),
+            ("npm", "isntall"),
+            ("npm", "--prefix /tmp/app install-cl"),
+            ("pnpm", "add eslint"),
+            ("pnpm", "up"),
+            ("yarn", ""),
+            ("yarn", "--frozen-lockfile"),
+            ("yarnpkg", "add eslint"),
+        ] {
+            assert!(installs(tool, args), "{tool} {args}");
+        }
+
+        for (tool, args) in [
+            ("npm", "view eslint"),
+            ("npm", "login"),
+            ("npm", "publish"),
+            ("npm", "--version"),
+            ("npm", "run build"),
+            ("npx", "eslint"),
+            ("pnpm", "exec tsc"),
+            ("yarn", "info eslint"),
+            ("yarn", "--version"),
+        ] {
+            assert!(!installs(tool, args), "{tool} {args}");
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use super::supervise;
+use crate::module::Context;
+use crate::npm::Snapshot;
+use crate::utils::command;
+use anyhow::Result;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+/// A command of a package manager that changes the global packages in its global directory,
+/// which is shared by the Node versions (e.g. 'pnpm add --global')
+pub trait SharedGlobalCommand<'a>: Sized {
+    /// Parse the arguments, none when they do not change the global packages
+    fn parse(args: &'a [OsString]) -> Option<Self>;
+
+    /// The global 'node_modules' of the package manager
+    fn global_root(&self, context: &Context, exe: &Path) -> Result<PathBuf>;
+
+    /// The directory the bins of the global packages are linked into,
+    /// none when it is the bin directory of the version
+    fn global_bin_dir(&self, context: &Context, exe: &Path) -> Result<Option<PathBuf>>;
+}
+
+/// Run a package manager installed under the version, and record the global packages
+/// a global command of it changed
+pub(super) fn command<'a, G>(exe: &OsStr, args: &'a [OsString]) -> Result<ExitStatus>
+where
+    G: SharedGlobalCommand<'a>,
+{
+    super::corepack::ensure_standalone(Context::global()?, exe)?;
+    let pinned_context = super::binary::pinned_context(exe)?;
+    let context = pinned_context.as_ref().unwrap_or(Context::global()?);
+    let path = context.lib_path(exe)?;
+
+    let mut command = command::create_command(&path);
+    command.args(args);
+    context.apply_env(&mut command)?;
+
+    let Some(global) = G::parse(args) else {
+        return super::exec(&mut command);
+    };
+
+    // Before command execution
+    let snapshot = Snapshot::read(global.global_root(context, &path).ok());
+
+    let status = supervise(&mut command)?;
+
+    // After the command is executed
+    if status.success() {
+        let after = Snapshot::read(global.global_root(context, &path).ok());
+        let bin_dir = global.global_bin_dir(context, &path)?;
+        snapshot.reconcile_shared(context, &after, bin_dir.as_deref())?;
+    }
+
+    Ok(status)
+}
@@ -0,0 +1,162 @@
+use super::{Setting, nvmd_home};
+use crate::utils::help::read_json;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+use tempfile::NamedTempFile;
+
+/// Drop the whole cache once it grows past this number of directories
+const MAX_ENTRIES: usize = 256;
+
+/// A cached directory-to-version lookup.
+/// It stays valid as long as none of the files it was derived from have been modified.
+#[derive(Debug, Deserialize, Serialize)]
+struct Entry {
+    /// the resolved version
+    version: String,
+
+    /// the version file (or the default file) the version was read from
+    source: PathBuf,
+    source_mtime: Option<u128>,
+
+    /// the settings the lookup was made with
+    setting_mtime: Option<u128>,
+    node_version_file: String,
+
+    /// the versions directory, modified whenever a version is installed or uninstalled
+    directory: PathBuf,
+    directory_mtime: Option<u128>,
+}
+
+type Cache = HashMap<PathBuf, Entry>;
+
+/// Look up the cached version for the directory,
+/// returns the version, its bin directory and the file it was read from
+pub(super) fn lookup(dir: &Path) -> Option<(String, PathBuf, PathBuf)> {
+    let home = nvmd_home().ok()?;
+    let mut cache = read_json::<Cache>(&home.version_cache_path()).ok()?;
+    let entry = cache.remove(dir)?;
+
+    if mtime(&home.setting_path()) != entry.setting_mtime
+        || mtime(&entry.directory) != entry.directory_mtime
+        || entry.source_mtime.is_none()
+        || mtime(&entry.source) != entry.source_mtime
+    {
+        return None;
+    }
+
+    if shadowed_by(dir, &entry.source, &entry.node_version_file) {
+        return None;
+    }
+
+    let mut bin_dir = entry.directory.join(&entry.version);
+    if cfg!(unix) {
+        bin_dir.push("bin");
+    }
+    Some((entry.version, bin_dir, entry.source))
+}
+
+/// Cache the version resolved for the directory, failures are ignored
+pub(super) fn store(dir: &Path, source: &Path, version: &str) {
+    let _ = try_store(dir, source, version);
+}
+
+fn try_store(dir: &Path, source: &Path, version: &str) -> anyhow::Result<()> {
+    let home = nvmd_home()?;
+    let setting = Setting::global()?;
+    let directory = setting.get_directory()?;
+    let path = home.version_cache_path();
+
+    let mut cache = read_json::<Cache>(&path).unwrap_or_default();
+    if cache.len() >= MAX_ENTRIES {
+        cache.clear();
+    }
+    cache.insert(
+        dir.to_path_buf(),
+        Entry {
+            version: version.to_string(),
+            source: source.to_path_buf(),
+            source_mtime: mtime(source),
+            setting_mtime: mtime(&home.setting_path()),
+            node_version_file: setting.get_node_version_file(),
+            directory_mtime: mtime(&directory),
+            directory,
+        },
+    );
+
+    // Many shims may run at the same time, so replace the file atomically
+    let cache_dir = home.cache_dir();
+    fs::create_dir_all(&cache_dir)?;
+    let mut file = NamedTempFile::new_in(&cache_dir)?;
+    file.write_all(serde_json::to_string(&cache)?.as_bytes())?;
+    file.persist(&path)?;
+    Ok(())
+}
+
+/// The modification times of the version file, the settings and the versions directory,
+/// a version resolved from the file is valid as long as the stamp does not change
+pub(super) fn stamp(source: &Path) -> Option<String> {
+    let setting_path = nvmd_home().ok()?.setting_path();
+    let directory = Setting::global().ok()?.get_directory().ok()?;
+    Some(format!(
+        "{}:{}:{}",
+        mtime(source)?,
+        mtime(&setting_path).unwrap_or_default(),
+        mtime(&directory).unwrap_or_default()
+    ))
+}
+
+/// Whether a version file was created closer to the directory than the source,
+/// it takes precedence
+pub(super) fn is_shadowed(dir: &Path, source: &Path) -> bool {
+    Setting::global()
+        .is_ok_and(|setting| shadowed_by(dir, source, &setting.get_node_version_file()))
+}
+
+fn shadowed_by(dir: &Path, source: &Path, file_name: &str) -> bool {
+    for ancestor in dir.ancestors() {
+        let path = ancestor.join(file_name);
+        if path == source {
+            return false;
+        }
+        if path.is_file() {
+            return true;
+        }
+    }
+    false
+}
+
+fn mtime(path: &Path) -> Option<u128> {
+    fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::shadowed_by;
+    use std::fs;
+
+    #[test]
+    fn closer_version_files_shadow_the_source() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("project");
+        let package = project.join("packages").join("app");
+        fs::create_dir_all(&package).unwrap();
+        let source = root.path().join(".nvmdrc");
+        fs::write(&source, "20").unwrap();
+
+        assert!(!shadowed_by(&package, &source, ".nvmdrc"));
+        fs::write(project.join(".nvmdrc"), "22").unwrap();
+        assert!(shadowed_by(&package, &source, ".nvmdrc"));
+        assert!(!shadowed_by(&package, &project.join(".nvmdrc"), ".nvmdrc"));
+    }
+}
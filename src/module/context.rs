@@ -1,43 +1,111 @@
-use super::nvmd_home;
-use crate::module::{NodeVersionResolver, Setting};
+use super::package::executable;
+use super::{cache, corepack, nvmd_home};
+use crate::module::{NodeVersionResolver, Packages, Setting, VersionFile};
 use anyhow::{Context as AnyhowContext, Result, anyhow, bail};
 use fs_extra::file::read_to_string;
 use once_cell::sync::OnceCell;
 use std::{
+    env,
     ffi::{OsStr, OsString},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    process::Command,
 };
 
+/// The version resolved by the parent shim
+const RESOLVED_VERSION_ENV: &str = "NVMD_RESOLVED_VERSION";
+/// The bin directory of the version resolved by the parent shim
+const RESOLVED_BIN_ENV: &str = "NVMD_RESOLVED_BIN";
+/// The directory the parent shim resolved the version for
+const RESOLVED_DIR_ENV: &str = "NVMD_RESOLVED_DIR";
+/// The version file the parent shim read the version from
+const RESOLVED_SOURCE_ENV: &str = "NVMD_RESOLVED_SOURCE";
+/// The modification times of the files the version was resolved with, see 'cache::stamp'
+const RESOLVED_STAMP_ENV: &str = "NVMD_RESOLVED_STAMP";
+
+/// Where the version of a context comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionSource {
+    /// The 'NVMD_NODE_VERSION' environment variable
+    Env,
+    /// The version resolved by the parent shim, from the version file
+    Parent(PathBuf),
+    /// A project version file, or the default file
+    File(PathBuf),
+    /// The version was specified explicitly
+    Specified,
+}
+
 pub struct Context {
     pub version: Option<String>,
+
+    /// The bin directory of the version, if it is already known
+    bin_dir: Option<PathBuf>,
+
+    source: Option<VersionSource>,
 }
 
 impl Context {
     pub fn global<'a>() -> Result<&'a Context> {
         static CONTEXT: OnceCell<Context> = OnceCell::new();
 
-        CONTEXT.get_or_try_init(|| {
-            let version = get_version()?;
-            Ok(Self { version })
-        })
+        CONTEXT.get_or_try_init(resolve)
+    }
+
+    /// Create a context for the specified version instead of resolving it
+    pub fn from_version(version: String) -> Self {
+        Self {
+            version: Some(version),
+            bin_dir: None,
+            source: Some(VersionSource::Specified),
+        }
     }
 
     pub fn get_version(&self) -> Option<String> {
         self.version.clone()
     }
 
-    pub fn env_path(&self) -> Result<OsString> {
-        let version = self.version
-            .clone()
-            .ok_or(
-             anyhow!("The default Node version is not set, you can set it by executing \"nvmd use {{version}}\"")
-            )?;
+    /// Where the version comes from, none if no version is set
+    pub fn source(&self) -> Option<&VersionSource> {
+        self.source.as_ref()
+    }
 
-        let mut path = super::Setting::global()?.get_directory()?.join(&version);
-        if cfg!(unix) {
-            path.push("bin");
+    /// The path of the executable of the tool under the version,
+    /// including the tools that were installed into another bin directory (e.g. by pnpm),
+    /// and the ones of the standalone corepack when the version does not bundle it
+    pub fn lib_path(&self, lib: &OsStr) -> Result<PathBuf> {
+        let (version, bin_dir) = self.bin_dir()?;
+        if let Some(path) = executable(&bin_dir, lib) {
+            return Ok(path);
         }
 
+        if let Some(path) = Packages::new()?.external_bin(&version, &lib.to_string_lossy()) {
+            return Ok(path);
+        }
+        match self.bundles_corepack() {
+            true => None,
+            false => corepack::standalone_executable(lib),
+        }
+        .ok_or_else(|| anyhow!("command not found: {:?}", lib))
+    }
+
+    /// Whether the version ships corepack, Node.js 25 and later no longer do
+    pub fn bundles_corepack(&self) -> bool {
+        self.bin_dir()
+            .is_ok_and(|(_, bin_dir)| executable(&bin_dir, "corepack").is_some())
+    }
+
+    pub fn env_path(&self) -> Result<OsString> {
+        self.prefixed_env_path(None)
+    }
+
+    /// The PATH with the directory before the bin directory of the version,
+    /// e.g. the bin directory of a release of npm to run instead of the bundled one
+    pub fn env_path_with(&self, dir: &Path) -> Result<OsString> {
+        self.prefixed_env_path(Some(dir))
+    }
+
+    fn prefixed_env_path(&self, dir: Option<&Path>) -> Result<OsString> {
+        let (version, path) = self.bin_dir()?;
         if !path.exists() {
             bail!(
                 "Node@v{} is not installed, please install it before using",
@@ -46,74 +114,177 @@ impl Context {
         }
 
         let old_env_path = envoy::path().unwrap_or_else(|| envoy::Var::from(""));
-        old_env_path
-            .split()
-            .prefix_entry(&path)
-            .join()
-            .with_context(|| {
-                anyhow!(
-                    "Could not create execution environment.\nPlease ensure your PATH is valid."
-                )
-            })
+        let mut entries = old_env_path.split().prefix_entry(&path);
+        if let Some(dir) = dir {
+            entries = entries.prefix_entry(dir);
+        }
+        entries.join().with_context(|| {
+            anyhow!("Could not create execution environment.\nPlease ensure your PATH is valid.")
+        })
+    }
+
+    /// Set the PATH of the command, and export the version resolved from a version file,
+    /// so the shims run by the command do not need to resolve it again.
+    /// The versions specified explicitly (e.g. pinned) are not exported.
+    pub fn apply_env(&self, command: &mut Command) -> Result<()> {
+        command.env("PATH", self.env_path()?);
+
+        let source = match &self.source {
+            Some(VersionSource::File(source) | VersionSource::Parent(source)) => source,
+            _ => return Ok(()),
+        };
+        let Some(stamp) = cache::stamp(source) else {
+            return Ok(());
+        };
+        let (version, bin_dir) = self.bin_dir()?;
+        command.env(RESOLVED_VERSION_ENV, version);
+        command.env(RESOLVED_BIN_ENV, bin_dir);
+        command.env(RESOLVED_DIR_ENV, env::current_dir()?);
+        command.env(RESOLVED_SOURCE_ENV, source);
+        command.env(RESOLVED_STAMP_ENV, stamp);
+        Ok(())
     }
 
     pub fn check_lib_path(&self, lib: &OsStr) -> Result<()> {
+        let (_, mut path) = self.bin_dir()?;
+        path.push(lib);
+
+        if !path.exists() {
+            bail!("command not found: {:?}", lib);
+        }
+        Ok(())
+    }
+
+    /// The bin directory of the version
+    pub fn bin_path(&self) -> Result<PathBuf> {
+        self.bin_dir().map(|(_, bin_dir)| bin_dir)
+    }
+
+    /// The installation directory of the version
+    pub fn install_dir(&self) -> Result<PathBuf> {
+        let (_, bin_dir) = self.bin_dir()?;
+        match cfg!(unix) {
+            true => bin_dir
+                .parent()
+                .map(Path::to_path_buf)
+                .ok_or_else(|| anyhow!("Invalid bin directory {:?}", bin_dir)),
+            false => Ok(bin_dir),
+        }
+    }
+
+    fn bin_dir(&self) -> Result<(String, PathBuf)> {
         let version = self.version
             .clone()
             .ok_or(
              anyhow!("The default Node version is not set, you can set it by executing \"nvmd use {{version}}\"")
             )?;
 
+        if let Some(bin_dir) = &self.bin_dir {
+            return Ok((version, bin_dir.clone()));
+        }
+
         let mut path = super::Setting::global()?.get_directory()?.join(&version);
         if cfg!(unix) {
             path.push("bin");
         }
-        path.push(lib);
-
-        if !path.exists() {
-            bail!("command not found: {:?}", lib);
-        }
-        Ok(())
+        Ok((version, path))
     }
 }
 
-fn get_version() -> Result<Option<String>> {
+fn resolve() -> Result<Context> {
     // 1. Check the NVMD_NODE_VERSION environment variable first
     if let Ok(env_version) = std::env::var("NVMD_NODE_VERSION") {
         let v = env_version.trim();
         if !v.is_empty() {
-            return Ok(Some(NodeVersionResolver::resolve(v)?));
+            return Ok(Context {
+                source: Some(VersionSource::Env),
+                ..Context::from_version(NodeVersionResolver::resolve(v)?)
+            });
         }
     }
 
-    // 2. Look for a .nvmdrc file in the current directory or its ancestors
-    //    If found, read its content and return the first non-empty value
-    if let Some(path) = find_nvmdrc()? {
-        let content = read_to_string(&path)?;
-        let t = content.trim();
-        if !t.is_empty() {
-            return Ok(Some(NodeVersionResolver::resolve(t)?));
+    // 2. Reuse the version resolved by the parent shim for the same directory,
+    //    then the cached lookup for the directory
+    let current_dir = env::current_dir()?;
+    if let Some(context) = inherited(&current_dir) {
+        return Ok(context);
+    }
+    if let Some((version, bin_dir, source)) = cache::lookup(&current_dir) {
+        return Ok(Context {
+            version: Some(version),
+            bin_dir: Some(bin_dir),
+            source: Some(VersionSource::File(source)),
+        });
+    }
+
+    // 3. Resolve the version from the version files and cache it
+    match get_version(&current_dir)? {
+        Some((version, source)) => {
+            cache::store(&current_dir, &source, &version);
+            Ok(Context {
+                source: Some(VersionSource::File(source)),
+                ..Context::from_version(version)
+            })
         }
+        None => Ok(Context {
+            version: None,
+            bin_dir: None,
+            source: None,
+        }),
+    }
+}
+
+/// The version resolved by the parent shim for the same directory,
+/// as long as the files it was resolved with have not been modified since
+fn inherited(current_dir: &Path) -> Option<Context> {
+    let dir = env::var_os(RESOLVED_DIR_ENV)?;
+    if Path::new(&dir) != current_dir {
+        return None;
+    }
+
+    let source = PathBuf::from(env::var_os(RESOLVED_SOURCE_ENV)?);
+    if env::var(RESOLVED_STAMP_ENV).ok() != cache::stamp(&source)
+        || cache::is_shadowed(current_dir, &source)
+    {
+        return None;
+    }
+
+    Some(Context {
+        version: Some(env::var(RESOLVED_VERSION_ENV).ok()?),
+        bin_dir: Some(PathBuf::from(env::var_os(RESOLVED_BIN_ENV)?)),
+        source: Some(VersionSource::Parent(source)),
+    })
+}
+
+/// Returns the version and the file it was read from
+fn get_version(current_dir: &Path) -> Result<Option<(String, PathBuf)>> {
+    // 1. Look for a .nvmdrc file in the current directory or its ancestors
+    //    If found, read the version of Node.js from it
+    if let Some(path) = find_nvmdrc(current_dir)?
+        && let Some(version) = VersionFile::read(&path)?.node
+    {
+        return Ok(Some((NodeVersionResolver::resolve(&version)?, path)));
     }
 
-    // 3. If .nvmdrc is missing or empty, check the default configuration file path
+    // 2. If .nvmdrc is missing or empty, check the default configuration file path
     //    Only proceed if the file exists and contains a non-empty value
     let default_path = nvmd_home()?.default_path();
     if default_path.is_file() {
         let content = read_to_string(&default_path)?;
         let t = content.trim();
         if !t.is_empty() {
-            return Ok(Some(NodeVersionResolver::resolve(t)?));
+            return Ok(Some((NodeVersionResolver::resolve(t)?, default_path)));
         }
     }
 
-    // 4. If no valid version is found, return None
+    // 3. If no valid version is found, return None
     Ok(None)
 }
 
-fn find_nvmdrc() -> Result<Option<PathBuf>> {
+/// The nearest version file, in the directory or its ancestors
+pub fn find_nvmdrc(current_dir: &Path) -> Result<Option<PathBuf>> {
     let file_name = Setting::global()?.get_node_version_file();
-    Ok(std::env::current_dir()?
+    Ok(current_dir
         .ancestors()
         .map(|dir| dir.join(&file_name))
         .find(|path| path.is_file()))
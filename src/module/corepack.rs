@@ -0,0 +1,25 @@
+use super::{NodeVersionResolver, Setting, executable, nvmd_home};
+use std::{ffi::OsStr, path::PathBuf};
+
+/// The tools a release of corepack provides
+const COREPACK_TOOLS: [&str; 5] = ["corepack", "pnpm", "pnpx", "yarn", "yarnpkg"];
+
+/// The executable of the tool in the standalone release of corepack,
+/// the latest one installed matching the 'corepack_version' setting (e.g. '0.29'),
+/// or the latest one installed without the setting
+pub fn standalone_executable(tool: &OsStr) -> Option<PathBuf> {
+    if !COREPACK_TOOLS.iter().any(|name| *name == tool) {
+        return None;
+    }
+
+    let dir = nvmd_home().ok()?.corepack_dir();
+    let version = Setting::global().ok()?.get_corepack_version();
+    let installed = NodeVersionResolver::latest_in(&dir, version.as_deref()).ok()??;
+
+    let prefix = dir.join(installed.to_string());
+    let bin_dir = match cfg!(windows) {
+        true => prefix,
+        false => prefix.join("bin"),
+    };
+    executable(&bin_dir, tool)
+}
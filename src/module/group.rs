@@ -50,10 +50,10 @@ impl Groups {
     }
 
     pub fn update(&mut self, name: &str, project_path: &str) {
-        if let Some(group) = self.data.iter_mut().find(|g| g.name == name) {
-            if !group.projects.iter().any(|p| p == project_path) {
-                group.projects.push(project_path.to_string());
-            }
+        if let Some(group) = self.data.iter_mut().find(|g| g.name == name)
+            && !group.projects.iter().any(|p| p == project_path)
+        {
+            group.projects.push(project_path.to_string());
         }
     }
 }
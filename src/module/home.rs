@@ -22,10 +22,6 @@ impl NvmdHome {
         Self(home)
     }
 
-    pub fn home(&self) -> &PathBuf {
-        &self.0
-    }
-
     pub fn bin_dir(&self) -> PathBuf {
         self.0.join("bin")
     }
@@ -53,6 +49,30 @@ impl NvmdHome {
     pub fn packages_path(&self) -> PathBuf {
         self.0.join("packages.json")
     }
+
+    pub fn pins_path(&self) -> PathBuf {
+        self.0.join("pins.json")
+    }
+
+    pub fn default_packages_path(&self) -> PathBuf {
+        self.0.join("default-packages")
+    }
+
+    pub fn npm_dir(&self) -> PathBuf {
+        self.0.join("npm")
+    }
+
+    pub fn corepack_dir(&self) -> PathBuf {
+        self.0.join("corepack")
+    }
+
+    pub fn cache_dir(&self) -> PathBuf {
+        self.0.join("cache")
+    }
+
+    pub fn version_cache_path(&self) -> PathBuf {
+        self.cache_dir().join("versions.json")
+    }
 }
 
 fn default_home_dir() -> Result<PathBuf> {
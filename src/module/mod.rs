@@ -1,15 +1,25 @@
+mod cache;
 mod context;
+mod corepack;
 mod group;
 mod home;
 mod package;
+mod package_manager;
+mod pin;
 mod project;
 mod setting;
 mod version;
+mod version_file;
 
-pub use context::Context;
+pub use context::{Context, VersionSource, find_nvmdrc};
 pub use group::Groups;
 pub use home::nvmd_home;
-pub use package::{PackageJson, Packages};
+pub use package::{
+    COREPACK_MANAGERS, PackageJson, PackageRecord, Packages, PackagesData, executable, project_bin,
+};
+pub use package_manager::{PackageManager, package_manager_of};
+pub use pin::Pins;
 pub use project::Projects;
 pub use setting::Setting;
 pub use version::*;
+pub use version_file::VersionFile;
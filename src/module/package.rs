@@ -1,70 +1,428 @@
 use super::nvmd_home;
 use crate::utils::help::{read_json, write_json};
-use anyhow::Result;
-use serde::Deserialize;
-use std::{collections::HashMap, env, ffi::OsStr, path::PathBuf};
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
 
-pub type PackagesData = HashMap<String, Vec<String>>;
+/// The global packages installed under each Node version, keyed by the version and the package name
+pub type PackagesData = BTreeMap<String, BTreeMap<String, PackageRecord>>;
+
+/// A global package installed under a Node version
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageRecord {
+    /// The version of the package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The bins the package provides, each one has a shim
+    #[serde(default)]
+    pub bins: Vec<String>,
+    /// The directory the bins are installed into,
+    /// when it is not the bin directory of the Node version (e.g. 'PNPM_HOME')
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bin_dir: Option<PathBuf>,
+}
+
+/// A package that provides a bin
+pub struct Owner<'a> {
+    /// None for the packages installed into a global directory shared by all the versions
+    pub node_version: Option<&'a str>,
+    pub name: &'a str,
+    pub record: &'a PackageRecord,
+}
+
+/// The format of 'packages.json' written by this version of nvmd
+const PACKAGES_FORMAT: u32 = 2;
+
+#[derive(Serialize, Deserialize)]
+struct PackagesFile {
+    format: u32,
+    #[serde(default)]
+    packages: PackagesData,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    shared: PackagesData,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredPackages {
+    Versioned(PackagesFile),
+    /// The former format, which only mapped the bins to the Node versions
+    Legacy(HashMap<String, Vec<String>>),
+}
+
+impl From<StoredPackages> for PackagesFile {
+    fn from(stored: StoredPackages) -> Self {
+        let packages = match stored {
+            StoredPackages::Versioned(file) => return file,
+            // The owning packages are unknown, so each bin stands for its own package
+            StoredPackages::Legacy(legacy) => {
+                let mut data = PackagesData::new();
+                for (bin, versions) in legacy {
+                    for version in versions {
+                        data.entry(version).or_default().insert(
+                            bin.clone(),
+                            PackageRecord {
+                                bins: vec![bin.clone()],
+                                ..Default::default()
+                            },
+                        );
+                    }
+                }
+                data
+            }
+        };
+        PackagesFile {
+            packages,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for PackagesFile {
+    fn default() -> Self {
+        Self {
+            format: PACKAGES_FORMAT,
+            packages: PackagesData::new(),
+            shared: PackagesData::new(),
+        }
+    }
+}
 
 pub struct Packages {
     path: PathBuf,
-    data: HashMap<String, Vec<String>>,
+    data: PackagesData,
+    /// The global packages installed into a directory shared by all the Node versions
+    /// (e.g. the global dir of pnpm), keyed by the directory and the package name
+    shared: PackagesData,
+    /// False when the file is in a format this version does not know, e.g. written by a newer one
+    writable: bool,
 }
 
 impl Packages {
+    /// Read 'packages.json', the former format is migrated when it is saved
     pub fn new() -> Result<Self> {
-        let path = nvmd_home()?.packages_path();
-        let data = read_json::<PackagesData>(&path).unwrap_or_default();
-        Ok(Self { path, data })
+        Self::read(nvmd_home()?.packages_path())
+    }
+
+    fn read(path: PathBuf) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::from_file(path, PackagesFile::default(), true));
+        }
+
+        let value = read_json::<serde_json::Value>(&path).ok();
+        let format = value
+            .as_ref()
+            .and_then(|value| value.get("format"))
+            .and_then(serde_json::Value::as_u64);
+        // a newer format is read as far as it can be, but never overwritten
+        if format.is_some_and(|format| format > PACKAGES_FORMAT as u64) {
+            let file = value
+                .and_then(|value| serde_json::from_value(value).ok())
+                .unwrap_or_default();
+            return Ok(Self::from_file(path, file, false));
+        }
+
+        match value.and_then(|value| serde_json::from_value::<StoredPackages>(value).ok()) {
+            Some(stored) => Ok(Self::from_file(path, stored.into(), true)),
+            // a corrupt or truncated file is set aside and started over
+            None => {
+                let backup = path.with_extension("json.bak");
+                fs::rename(&path, &backup).with_context(|| {
+                    format!("Failed to read \"{}\", please remove it", path.display())
+                })?;
+                eprintln!(
+                    "{}",
+                    console::style(format!(
+                        "\"{}\" could not be read, it was moved to \"{}\".\n\
+                         Run \"nvmd shim sync\" to rebuild it.",
+                        path.display(),
+                        backup.display()
+                    ))
+                    .yellow()
+                );
+                Ok(Self::from_file(path, PackagesFile::default(), true))
+            }
+        }
     }
 
+    fn from_file(path: PathBuf, file: PackagesFile, writable: bool) -> Self {
+        Self {
+            path,
+            data: file.packages,
+            shared: file.shared,
+            writable,
+        }
+    }
+
+    /// Write 'packages.json', a file in an unknown format is never overwritten
     pub fn save(&self) -> Result<()> {
-        write_json(&self.path, &self.data)
-    }
-
-    pub fn record_installed(&mut self, packages: &[String], version: &str) {
-        for pkg in packages {
-            // Remove the specified version first
-            self.data
-                .entry(pkg.clone())
-                .or_default()
-                .retain(|v| v != version);
-            // Then add the specified version
-            self.data
-                .entry(pkg.clone())
-                .or_default()
-                .push(version.to_string());
+        if !self.writable {
+            bail!(
+                "\"{}\" is in a format this version of nvmd does not know, it is left unchanged.\n\
+                 Please upgrade nvmd, or remove the file and run \"nvmd shim sync\" to rebuild it.",
+                self.path.display()
+            );
         }
+
+        write_json(
+            &self.path,
+            &PackagesFile {
+                format: PACKAGES_FORMAT,
+                packages: self.data.clone(),
+                shared: self.shared.clone(),
+            },
+        )
+    }
+
+    /// Record the package installed under the Node version, replacing the former record
+    pub fn record_installed(
+        &mut self,
+        node_version: &str,
+        name: &str,
+        package: &PackageJson,
+    ) -> &mut PackageRecord {
+        insert(&mut self.data, node_version, name, package)
+    }
+
+    /// Record the package installed into the global directory shared by all the Node versions
+    pub fn record_shared_installed(
+        &mut self,
+        global_dir: &Path,
+        name: &str,
+        package: &PackageJson,
+    ) -> &mut PackageRecord {
+        insert(
+            &mut self.shared,
+            &global_dir.to_string_lossy(),
+            name,
+            package,
+        )
+    }
+
+    /// Drop the packages uninstalled from the Node version.
+    /// Returns the bins that are no longer provided by any package.
+    pub fn record_uninstalled(
+        &mut self,
+        node_version: &str,
+        packages: &[PackageJson],
+    ) -> Vec<String> {
+        let removed = remove(&mut self.data, node_version, packages);
+        self.orphaned(removed)
+    }
+
+    /// Drop the packages uninstalled from the shared global directory.
+    /// Returns the bins that are no longer provided by any package.
+    pub fn record_shared_uninstalled(
+        &mut self,
+        global_dir: &Path,
+        packages: &[PackageJson],
+    ) -> Vec<String> {
+        let removed = remove(&mut self.shared, &global_dir.to_string_lossy(), packages);
+        self.orphaned(removed)
+    }
+
+    /// Drop every package installed under the Node version.
+    /// Returns the bins that were provided by this version and no longer have any provider.
+    pub fn record_version_removed(&mut self, node_version: &str) -> Vec<String> {
+        let removed = self
+            .data
+            .remove(node_version)
+            .unwrap_or_default()
+            .into_values()
+            .flat_map(|record| record.bins)
+            .collect();
+        self.orphaned(removed)
     }
 
-    pub fn record_uninstalled(&mut self, packages: &[String], version: &str) -> Vec<String> {
-        let mut to_remove = Vec::new();
-        for pkg in packages {
-            if let Some(versions) = self.data.get_mut(pkg) {
-                versions.retain(|v| v != version);
-                if versions.is_empty() {
-                    to_remove.push(pkg.clone());
+    /// Replace the packages of the Node versions, e.g. with the result of scanning them.
+    /// The packages installed into another bin directory are not scanned,
+    /// so they are kept as long as their bins still exist.
+    pub fn rebuild(&mut self, mut data: PackagesData) {
+        for (node_version, packages) in &self.data {
+            for (name, record) in packages {
+                if record.bin_dir.is_some() && bins_exist(record) {
+                    data.entry(node_version.clone())
+                        .or_default()
+                        .insert(name.clone(), record.clone());
                 }
-            } else {
-                to_remove.push(pkg.clone());
             }
         }
-        to_remove
+        self.data = data;
+
+        for packages in self.shared.values_mut() {
+            packages.retain(|_, record| bins_exist(record));
+        }
+        self.shared.retain(|_, packages| !packages.is_empty());
+    }
+
+    /// The executable of the bin, when the package providing it under the Node version
+    /// installed it into another bin directory, or into a shared global directory
+    pub fn external_bin(&self, node_version: &str, bin: &str) -> Option<PathBuf> {
+        self.data
+            .get(node_version)
+            .into_iter()
+            .chain(self.shared.values())
+            .flat_map(BTreeMap::values)
+            .filter(|record| record.bins.iter().any(|b| b == bin))
+            .find_map(|record| executable(record.bin_dir.as_ref()?, bin))
+    }
+
+    /// The Node versions that provide the bin
+    pub fn versions(&self, bin: &str) -> Vec<&String> {
+        let versions = self
+            .owners(bin)
+            .into_iter()
+            .filter_map(|owner| owner.node_version)
+            .collect::<Vec<_>>();
+        self.data
+            .keys()
+            .filter(|version| versions.contains(&version.as_str()))
+            .collect()
+    }
+
+    /// All the bins provided by the recorded packages
+    pub fn bins(&self) -> BTreeSet<&String> {
+        self.data
+            .values()
+            .chain(self.shared.values())
+            .flat_map(BTreeMap::values)
+            .flat_map(|record| &record.bins)
+            .collect()
+    }
+
+    /// The packages that provide the bin, under any Node version or shared global directory
+    pub fn owners(&self, bin: &str) -> Vec<Owner<'_>> {
+        let versions = self
+            .data
+            .iter()
+            .map(|(node_version, packages)| (Some(node_version.as_str()), packages));
+        let shared = self.shared.values().map(|packages| (None, packages));
+        versions
+            .chain(shared)
+            .flat_map(|(node_version, packages)| {
+                packages
+                    .iter()
+                    .filter(|(_, record)| record.bins.iter().any(|b| b == bin))
+                    .map(move |(name, record)| Owner {
+                        node_version,
+                        name,
+                        record,
+                    })
+            })
+            .collect()
+    }
+
+    /// The shim of the bin can be removed once no package provides it anymore
+    pub fn can_be_removed(&self, bin: &str) -> bool {
+        self.owners(bin).is_empty()
+    }
+
+    fn orphaned(&self, mut bins: Vec<String>) -> Vec<String> {
+        bins.sort();
+        bins.dedup();
+        bins.retain(|bin| self.can_be_removed(bin));
+        bins
+    }
+}
+
+/// Record the package under the key, replacing the former record
+fn insert<'a>(
+    data: &'a mut PackagesData,
+    key: &str,
+    name: &str,
+    package: &PackageJson,
+) -> &'a mut PackageRecord {
+    let packages = data.entry(key.to_string()).or_default();
+    let bins = package.bin_names();
+    // the bins of a package recorded in the former format now belong to this package
+    packages.retain(|pkg, record| !(record.version.is_none() && bins.contains(pkg)));
+    packages.insert(
+        name.to_string(),
+        PackageRecord {
+            version: package.version.clone(),
+            bins,
+            bin_dir: None,
+        },
+    );
+    packages.get_mut(name).unwrap()
+}
+
+/// Drop the packages recorded under the key, returns the bins they provided
+fn remove(data: &mut PackagesData, key: &str, packages: &[PackageJson]) -> Vec<String> {
+    let mut removed = vec![];
+    if let Some(installed) = data.get_mut(key) {
+        for package in packages {
+            let name = package.name.clone().unwrap_or_default();
+            let bins = package.bin_names();
+            installed.retain(|pkg, record| {
+                let matched = *pkg == name || (record.version.is_none() && bins.contains(pkg));
+                if matched {
+                    removed.extend(record.bins.iter().cloned());
+                }
+                !matched
+            });
+        }
+        if installed.is_empty() {
+            data.remove(key);
+        }
     }
+    removed
+}
+
+fn bins_exist(record: &PackageRecord) -> bool {
+    record.bin_dir.as_ref().is_some_and(|bin_dir| {
+        record
+            .bins
+            .iter()
+            .all(|bin| executable(bin_dir, bin).is_some())
+    })
+}
 
-    pub fn can_be_removed(&self, name: &str) -> bool {
-        self.data.get(name).map(|v| v.is_empty()).unwrap_or(true)
+/// The executable of the bin in the directory, with the extensions it may have on Windows
+pub fn executable(dir: &Path, bin: impl AsRef<OsStr>) -> Option<PathBuf> {
+    let mut names = vec![];
+    if cfg!(windows) {
+        for ext in [".exe", ".cmd"] {
+            let mut name = bin.as_ref().to_os_string();
+            name.push(ext);
+            names.push(name);
+        }
     }
+    names.push(bin.as_ref().to_os_string());
+
+    names
+        .into_iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists())
+}
+
+/// The executable of the tool in the nearest 'node_modules/.bin', in the directory or its ancestors
+pub fn project_bin(dir: &Path, tool: impl AsRef<OsStr>) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join("node_modules").join(".bin"))
+        .find_map(|bin_dir| executable(&bin_dir, tool.as_ref()))
 }
 
-#[derive(Debug, Default, Clone, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
 pub struct PackageJson {
     pub name: Option<String>,
+    pub version: Option<String>,
     pub bin: Option<Bin>,
+    /// The package manager the project uses, e.g. 'pnpm@9.1.0'
+    #[serde(rename = "packageManager")]
+    pub package_manager: Option<String>,
 }
 
+/// The package managers corepack can enable
+pub const COREPACK_MANAGERS: [&str; 3] = ["npm", "pnpm", "yarn"];
+
 /// https://docs.npmjs.com/cli/v10/configuring-npm/package-json#bin
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 #[serde(untagged)]
 pub enum Bin {
     Single(String),
@@ -72,29 +430,233 @@ pub enum Bin {
 }
 
 impl PackageJson {
-    pub fn new(prefix: &PathBuf, pkg: impl AsRef<OsStr>) -> Self {
+    pub fn new(prefix: &Path, pkg: impl AsRef<OsStr>) -> Self {
         let path = prefix.join(pkg.as_ref()).join("package.json");
         read_json::<PackageJson>(&path).unwrap_or_default()
     }
 
-    pub fn from_current_dir() -> Result<Self> {
-        let path = env::current_dir()?.join("package.json").canonicalize()?;
-        read_json::<PackageJson>(&path)
-    }
-
-    pub fn from_current_dir_with_pkg(pkg: impl AsRef<OsStr>) -> Result<Self> {
-        let path = env::current_dir()?
-            .join(pkg.as_ref())
-            .join("package.json")
-            .canonicalize()?;
-        read_json::<PackageJson>(&path)
+    /// The package standing for the bins corepack enables for the package manager under a version,
+    /// recorded as 'corepack:<manager>'. The bins of npm are core shims, so it has none.
+    pub fn corepack(manager: &str) -> Self {
+        let bins: &[&str] = match manager {
+            "pnpm" => &["pnpm", "pnpx"],
+            "yarn" => &["yarn", "yarnpkg"],
+            _ => &[],
+        };
+        Self {
+            name: Some(format!("corepack:{manager}")),
+            bin: (!bins.is_empty()).then(|| {
+                Bin::Multiple(
+                    bins.iter()
+                        .map(|bin| (bin.to_string(), "corepack".to_string()))
+                        .collect(),
+                )
+            }),
+            ..Default::default()
+        }
     }
 
     pub fn bin_names(&self) -> Vec<String> {
         match &self.bin {
-            Some(Bin::Single(_bin)) => vec![self.name.clone().unwrap_or_default()],
+            // A single bin of a scoped package is named without the scope
+            Some(Bin::Single(_bin)) => {
+                let name = self.name.clone().unwrap_or_default();
+                match name.split_once('/') {
+                    Some((scope, bin)) if scope.starts_with('@') => vec![bin.to_string()],
+                    _ => vec![name],
+                }
+            }
             Some(Bin::Multiple(map)) => map.keys().cloned().collect(),
             None => vec![],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Bin, PACKAGES_FORMAT, PackageJson, Packages, PackagesData, PackagesFile, StoredPackages,
+        project_bin,
+    };
+    use std::{fs, path::PathBuf};
+
+    fn package(name: &str, bins: &[&str]) -> PackageJson {
+        PackageJson {
+            name: Some(name.to_string()),
+            version: Some("1.0.0".to_string()),
+            bin: Some(Bin::Multiple(
+                bins.iter()
+                    .map(|bin| (bin.to_string(), format!("bin/{bin}")))
+                    .collect(),
+            )),
+            ..Default::default()
+        }
+    }
+
+    fn packages() -> Packages {
+        let mut packages = Packages {
+            path: PathBuf::new(),
+            data: PackagesData::new(),
+            shared: PackagesData::new(),
+            writable: true,
+        };
+        let typescript = package("typescript", &["tsc", "tsserver"]);
+        packages.record_installed("18.20.3", "typescript", &typescript);
+        packages.record_installed("20.11.1", "typescript", &typescript);
+        packages.record_installed("20.11.1", "pnpm", &package("pnpm", &["pnpm", "pnpx"]));
+        packages.record_installed("20.11.1", "tsc-shim", &package("tsc-shim", &["tsc"]));
+        packages
+    }
+
+    #[test]
+    fn remove_version_returns_bins_without_provider() {
+        let mut packages = packages();
+
+        assert_eq!(
+            packages.record_version_removed("20.11.1"),
+            vec!["pnpm", "pnpx"]
+        );
+        assert!(packages.can_be_removed("pnpm"));
+        assert!(!packages.can_be_removed("tsc"));
+        assert_eq!(packages.versions("tsc"), vec!["18.20.3"]);
+    }
+
+    #[test]
+    fn uninstall_keeps_bins_provided_by_other_packages() {
+        let mut packages = packages();
+
+        let removed = packages.record_uninstalled("20.11.1", &[package("tsc-shim", &["tsc"])]);
+        assert!(removed.is_empty());
+        assert_eq!(packages.owners("tsc").len(), 2);
+
+        let removed = packages.record_uninstalled("18.20.3", &[package("typescript", &["tsc"])]);
+        assert!(removed.is_empty());
+        let removed = packages.record_uninstalled("20.11.1", &[package("typescript", &["tsc"])]);
+        assert_eq!(removed, vec!["tsc", "tsserver"]);
+    }
+
+    #[test]
+    fn corepack_disable_keeps_bins_used_by_other_versions() {
+        let mut packages = packages();
+        let pnpm = PackageJson::corepack("pnpm");
+        packages.record_installed("18.20.3", "corepack:pnpm", &pnpm);
+
+        assert!(packages.record_uninstalled("18.20.3", &[pnpm]).is_empty());
+        assert_eq!(
+            packages.record_uninstalled("20.11.1", &[package("pnpm", &["pnpm", "pnpx"])]),
+            vec!["pnpm", "pnpx"]
+        );
+        assert!(PackageJson::corepack("npm").bin_names().is_empty());
+    }
+
+    #[test]
+    fn find_the_nearest_project_bin() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("packages").join("app");
+        fs::create_dir_all(nested.join("node_modules").join(".bin")).unwrap();
+        fs::create_dir_all(root.path().join("node_modules").join(".bin")).unwrap();
+        fs::write(root.path().join("node_modules/.bin/eslint"), "").unwrap();
+
+        assert_eq!(
+            project_bin(&nested.join("src"), "eslint"),
+            Some(root.path().join("node_modules/.bin/eslint"))
+        );
+        assert_eq!(project_bin(&nested, "tsc"), None);
+    }
+
+    #[test]
+    fn read_the_former_format() {
+        let stored =
+            serde_json::from_str::<StoredPackages>(r#"{"tsc":["18.20.3","20.11.1"]}"#).unwrap();
+        let mut packages = Packages {
+            path: PathBuf::new(),
+            data: PackagesFile::from(stored).packages,
+            shared: PackagesData::new(),
+            writable: true,
+        };
+        assert_eq!(packages.versions("tsc"), vec!["18.20.3", "20.11.1"]);
+
+        // the placeholder is replaced by the package that really provides the bin
+        let typescript = package("typescript", &["tsc", "tsserver"]);
+        packages.record_installed("20.11.1", "typescript", &typescript);
+        assert_eq!(packages.owners("tsc").len(), 2);
+        assert_eq!(
+            packages.record_uninstalled("18.20.3", &[typescript]),
+            Vec::<String>::new()
+        );
+        assert_eq!(packages.versions("tsc"), vec!["20.11.1"]);
+    }
+
+    #[test]
+    fn read_the_recorded_format() {
+        let packages = packages();
+        let json = serde_json::to_string(&PackagesFile {
+            format: PACKAGES_FORMAT,
+            packages: packages.data.clone(),
+            shared: PackagesData::new(),
+        })
+        .unwrap();
+        assert!(json.starts_with(r#"{"format":2,"#));
+
+        let stored = serde_json::from_str::<StoredPackages>(&json).unwrap();
+        assert!(matches!(stored, StoredPackages::Versioned(_)));
+        assert_eq!(PackagesFile::from(stored).packages, packages.data);
+    }
+
+    #[test]
+    fn set_corrupt_files_aside_and_keep_newer_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("packages.json");
+
+        fs::write(&path, r#"{"format":2,"packages":{"#).unwrap();
+        let packages = Packages::read(path.clone()).unwrap();
+        assert!(packages.writable && packages.data.is_empty());
+        assert!(!path.exists() && dir.path().join("packages.json.bak").exists());
+        packages.save().unwrap();
+        assert!(matches!(
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap(),
+            StoredPackages::Versioned(_)
+        ));
+
+        fs::write(&path, r#"{"format":3,"packages":[]}"#).unwrap();
+        let packages = Packages::read(path.clone()).unwrap();
+        assert!(!packages.writable);
+        assert!(packages.save().is_err());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            r#"{"format":3,"packages":[]}"#
+        );
+    }
+
+    #[test]
+    fn shared_packages_belong_to_every_version() {
+        let root = tempfile::tempdir().unwrap();
+        let global_dir = root.path().join("global").join("5").join("node_modules");
+        let bin_dir = root.path().join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        fs::write(bin_dir.join("vue"), "").unwrap();
+
+        let mut packages = packages();
+        packages
+            .record_shared_installed(&global_dir, "@vue/cli", &package("@vue/cli", &["vue"]))
+            .bin_dir = Some(bin_dir.clone());
+
+        assert_eq!(
+            packages.external_bin("22.1.0", "vue"),
+            Some(bin_dir.join("vue"))
+        );
+        assert!(packages.versions("vue").is_empty());
+        assert!(
+            packages
+                .record_version_removed("20.11.1")
+                .iter()
+                .all(|bin| bin != "vue")
+        );
+        assert!(!packages.can_be_removed("vue"));
+
+        let removed =
+            packages.record_shared_uninstalled(&global_dir, &[package("@vue/cli", &["vue"])]);
+        assert_eq!(removed, vec!["vue"]);
+        assert!(packages.shared.is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+use super::PackageJson;
+use std::path::{Path, PathBuf};
+
+/// The package manager a project declares in the 'packageManager' field of its 'package.json',
+/// e.g. 'pnpm@9.1.0+sha512.<hash>'
+#[derive(Debug, PartialEq)]
+pub struct PackageManager {
+    pub name: String,
+    pub version: String,
+    /// The 'package.json' declaring it
+    pub path: PathBuf,
+}
+
+impl PackageManager {
+    /// The package manager declared by the nearest 'package.json' that declares one,
+    /// in the directory or its ancestors
+    pub fn find(dir: &Path) -> Option<Self> {
+        dir.ancestors()
+            .filter(|dir| dir.join("package.json").is_file())
+            .find_map(|dir| {
+                let field = PackageJson::new(dir, "").package_manager?;
+                Self::parse(&field, dir.join("package.json"))
+            })
+    }
+
+    fn parse(field: &str, path: PathBuf) -> Option<Self> {
+        let (name, version) = field.trim().split_once('@')?;
+        // the hash corepack checks the download with is not part of the version
+        let version = version
+            .split_once('+')
+            .map_or(version, |(version, _)| version);
+        if name.is_empty() || version.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            path,
+        })
+    }
+
+    /// Whether the tool is one of the package manager, e.g. 'yarnpkg' for yarn
+    pub fn provides(&self, tool: &str) -> bool {
+        package_manager_of(tool) == Some(self.name.as_str())
+    }
+}
+
+/// The package manager the tool belongs to, none if it is not a package manager
+pub fn package_manager_of(tool: &str) -> Option<&'static str> {
+    match tool {
+        "npm" => Some("npm"),
+        "pnpm" => Some("pnpm"),
+        "yarn" | "yarnpkg" => Some("yarn"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PackageManager;
+    use std::path::PathBuf;
+
+    fn parse(field: &str) -> Option<(String, String)> {
+        PackageManager::parse(field, PathBuf::new()).map(|pm| (pm.name, pm.version))
+    }
+
+    #[test]
+    fn parse_the_package_manager_field() {
+        let pnpm = Some(("pnpm".to_string(), "9.1.0".to_string()));
+        assert_eq!(parse("pnpm@9.1.0"), pnpm);
+        assert_eq!(parse("pnpm@9.1.0+sha512.abcdef"), pnpm);
+        assert_eq!(parse("pnpm"), None);
+        assert_eq!(parse("@9.1.0"), None);
+
+        let yarn = PackageManager::parse("yarn@4.2.2", PathBuf::new()).unwrap();
+        assert!(yarn.provides("yarnpkg"));
+        assert!(!yarn.provides("npm"));
+    }
+}
@@ -0,0 +1,72 @@
+use super::{Context, Packages, Setting, nvmd_home};
+use crate::utils::help::{read_json, write_json};
+use anyhow::Result;
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// The versions global tools are pinned to, by the user
+#[derive(Debug)]
+pub struct Pins {
+    pub path: PathBuf,
+    pub data: BTreeMap<String, String>,
+}
+
+impl Pins {
+    pub fn new() -> Result<Self> {
+        let path = nvmd_home()?.pins_path();
+        let data = read_json::<BTreeMap<String, String>>(&path).unwrap_or_default();
+        Ok(Self { path, data })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        write_json(&self.path, &self.data)
+    }
+
+    pub fn get(&self, tool: &str) -> Option<&String> {
+        self.data.get(tool)
+    }
+
+    pub fn pin(&mut self, tool: &str, version: &str) {
+        self.data.insert(tool.to_string(), version.to_string());
+    }
+
+    pub fn unpin(&mut self, tool: &str) -> bool {
+        self.data.remove(tool).is_some()
+    }
+
+    /// The version a global tool runs with instead of the active version.
+    ///
+    /// Only applies when the tool is missing from the active version, or always when the
+    /// 'pin_tools' setting is on: the version the tool is pinned to, otherwise the newest
+    /// version recorded in 'packages.json' that still provides the tool.
+    pub fn resolve(tool: &str, active: &Context) -> Result<Option<String>> {
+        if active.lib_path(tool.as_ref()).is_ok() && !Setting::global()?.get_pin_tools() {
+            return Ok(None);
+        }
+
+        if let Some(version) = Self::new()?.get(tool)
+            && provides(version, tool)
+        {
+            return Ok(Some(version.clone()));
+        }
+
+        Ok(Self::candidates(tool, &Packages::new()?).into_iter().next())
+    }
+
+    /// The installed versions that provide the tool, the newest first
+    pub fn candidates(tool: &str, packages: &Packages) -> Vec<String> {
+        let mut versions = packages
+            .versions(tool)
+            .iter()
+            .filter(|version| provides(version, tool))
+            .filter_map(|version| semver::Version::parse(version).ok())
+            .collect::<Vec<_>>();
+        versions.sort();
+        versions.iter().rev().map(|v| v.to_string()).collect()
+    }
+}
+
+fn provides(version: &str, tool: &str) -> bool {
+    Context::from_version(version.to_string())
+        .lib_path(tool.as_ref())
+        .is_ok()
+}
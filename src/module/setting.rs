@@ -20,6 +20,36 @@ pub struct Setting {
     /// embed server port
     /// default: 53333
     pub embed_server_port: Option<u16>,
+
+    /// npm packages installed globally into every newly installed version
+    pub default_packages: Option<Vec<String>>,
+
+    /// hard-link identical files across versions after installing a new version
+    /// default: false
+    pub dedupe_on_install: Option<bool>,
+
+    /// run global tools with the version they are pinned to (or the newest version providing them)
+    /// even when the active version provides them too
+    /// default: false
+    pub pin_tools: Option<bool>,
+
+    /// the release of corepack installed for the versions that no longer bundle it
+    /// default: the latest
+    pub corepack_version: Option<String>,
+
+    /// refuse to run another package manager than the one of the 'packageManager' field
+    /// of the project instead of warning
+    /// default: false
+    pub package_manager_strict: Option<bool>,
+
+    /// run the tools a project depends on from its 'node_modules/.bin' before the global ones
+    /// default: true
+    pub prefer_local_bins: Option<bool>,
+
+    /// raise the signal that terminated a tool again on the shim, so its parent sees the same
+    /// termination, instead of only exiting with 128 + the signal number
+    /// default: true
+    pub reraise_signals: Option<bool>,
 }
 
 impl Setting {
@@ -36,6 +66,13 @@ impl Setting {
                     mirror: Some("https://nodejs.org/dist".into()),
                     node_version_file: Some(".nvmdrc".into()),
                     embed_server_port: Some(53333),
+                    default_packages: None,
+                    dedupe_on_install: Some(false),
+                    pin_tools: Some(false),
+                    corepack_version: None,
+                    package_manager_strict: Some(false),
+                    prefer_local_bins: Some(true),
+                    reraise_signals: Some(true),
                 }),
             }
         })
@@ -61,4 +98,34 @@ impl Setting {
     pub fn get_embed_server_port(&self) -> u16 {
         self.embed_server_port.unwrap_or(53333)
     }
+
+    pub fn get_default_packages(&self) -> Vec<String> {
+        self.default_packages.clone().unwrap_or_default()
+    }
+
+    pub fn get_dedupe_on_install(&self) -> bool {
+        self.dedupe_on_install.unwrap_or(false)
+    }
+
+    pub fn get_pin_tools(&self) -> bool {
+        self.pin_tools.unwrap_or(false)
+    }
+
+    pub fn get_package_manager_strict(&self) -> bool {
+        self.package_manager_strict.unwrap_or(false)
+    }
+
+    pub fn get_prefer_local_bins(&self) -> bool {
+        self.prefer_local_bins.unwrap_or(true)
+    }
+
+    pub fn get_reraise_signals(&self) -> bool {
+        self.reraise_signals.unwrap_or(true)
+    }
+
+    pub fn get_corepack_version(&self) -> Option<String> {
+        self.corepack_version
+            .as_deref()
+            .map(|version| version.trim_start_matches('v').to_string())
+    }
 }
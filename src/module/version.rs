@@ -1,5 +1,5 @@
 use anyhow::{Context as _, Result, anyhow};
-use std::fs;
+use std::{fs, path::Path};
 
 use crate::module::Setting;
 
@@ -10,6 +10,18 @@ enum NodeVersionRequest {
     MajorMinor(u64, u64),
 }
 
+impl NodeVersionRequest {
+    fn matches(&self, version: &semver::Version) -> bool {
+        match self {
+            NodeVersionRequest::Major(major) => version.major == *major,
+            NodeVersionRequest::MajorMinor(major, minor) => {
+                version.major == *major && version.minor == *minor
+            }
+            NodeVersionRequest::Exact(exact) => version == exact,
+        }
+    }
+}
+
 pub struct NodeVersionResolver;
 
 impl NodeVersionResolver {
@@ -36,6 +48,22 @@ impl NodeVersionResolver {
     /// 14.18.3  -> 14.18.3
     pub fn resolve(input: &str) -> Result<String> {
         let request = Self::parse_request(input)?;
+        if let NodeVersionRequest::Exact(version) = &request {
+            let version = version.to_string();
+            if Setting::global()?.get_directory()?.join(&version).is_dir() {
+                return Ok(version);
+            }
+        }
+
+        let versions = Self::installed()?;
+
+        Self::latest_matching(&request, versions)
+            .map(|version| version.to_string())
+            .ok_or_else(|| anyhow!("Node@v{} has not been installed", input))
+    }
+
+    /// List all installed node versions.
+    pub fn installed() -> Result<Vec<semver::Version>> {
         let versions_dir = Setting::global()?.get_directory()?;
         let versions = fs::read_dir(&versions_dir)
             .with_context(|| {
@@ -56,9 +84,37 @@ impl NodeVersionResolver {
             })
             .collect::<Vec<_>>();
 
-        Self::latest_matching(&request, versions)
-            .map(|version| version.to_string())
-            .ok_or_else(|| anyhow!("Node@v{} has not been installed", input))
+        Ok(versions)
+    }
+
+    /// The latest release installed in the directory, under a directory named after its version,
+    /// that matches the user input, or the latest one without input.
+    ///
+    /// Examples:
+    /// 10       -> 10.9.0
+    /// 10.8     -> 10.8.2
+    pub fn latest_in(dir: &Path, input: Option<&str>) -> Result<Option<semver::Version>> {
+        let request = input.map(Self::parse_request).transpose()?;
+        let versions = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| Self::parse(entry.ok()?.file_name().to_str()?).ok())
+            .collect::<Vec<_>>();
+
+        Ok(match request {
+            Some(request) => Self::latest_matching(&request, versions),
+            None => versions.into_iter().max(),
+        })
+    }
+
+    /// Check whether the user input matches the version.
+    ///
+    /// Examples:
+    /// 14       matches 14.21.3
+    /// 14.18    matches 14.18.3
+    /// 14.18.3  matches 14.18.3
+    pub fn matches(input: &str, version: &semver::Version) -> Result<bool> {
+        Ok(Self::parse_request(input)?.matches(version))
     }
 
     fn parse_request(input: &str) -> Result<NodeVersionRequest> {
@@ -98,13 +154,7 @@ impl NodeVersionResolver {
         request: &NodeVersionRequest,
         mut versions: Vec<semver::Version>,
     ) -> Option<semver::Version> {
-        versions.retain(|version| match request {
-            NodeVersionRequest::Major(major) => version.major == *major,
-            NodeVersionRequest::MajorMinor(major, minor) => {
-                version.major == *major && version.minor == *minor
-            }
-            NodeVersionRequest::Exact(exact) => version == exact,
-        });
+        versions.retain(|version| request.matches(version));
         versions.sort();
         versions.pop()
     }
@@ -161,4 +211,24 @@ mod tests {
             "14.18.3"
         );
     }
+
+    #[test]
+    fn choose_latest_release_installed_in_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["9.9.4", "10.8.2", "10.9.0", ".install-abc"] {
+            std::fs::create_dir(dir.path().join(name)).unwrap();
+        }
+        let latest = |input| {
+            NodeVersionResolver::latest_in(dir.path(), input)
+                .unwrap()
+                .map(|version| version.to_string())
+        };
+
+        assert_eq!(latest(Some("10")).as_deref(), Some("10.9.0"));
+        assert_eq!(latest(Some("10.8")).as_deref(), Some("10.8.2"));
+        assert_eq!(latest(Some("v9.9.4")).as_deref(), Some("9.9.4"));
+        assert_eq!(latest(Some("11")), None);
+        assert_eq!(latest(None).as_deref(), Some("10.9.0"));
+        assert!(NodeVersionResolver::latest_in(dir.path(), Some("^10")).is_err());
+    }
 }
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use fs_extra::file::{read_to_string, write_all};
+use std::{collections::BTreeMap, path::Path};
+
+/// A project version file (e.g. '.nvmdrc').
+///
+/// It holds the version of Node.js, optionally followed by the versions of other tools,
+/// one 'tool=version' per line:
+///
+/// ```text
+/// 20.11.1
+/// npm=10.8.2
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub struct VersionFile {
+    /// The version of Node.js
+    pub node: Option<String>,
+    fields: BTreeMap<String, String>,
+}
+
+impl VersionFile {
+    pub fn parse(content: &str) -> Self {
+        let mut file = Self::default();
+        for line in content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            match line.split_once('=') {
+                Some((key, value)) => {
+                    file.fields
+                        .insert(key.trim().to_string(), value.trim().to_string());
+                }
+                None if file.node.is_none() => file.node = Some(line.to_string()),
+                None => {}
+            }
+        }
+        file
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        Ok(Self::parse(&read_to_string(path)?))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let mut lines = self.node.iter().cloned().collect::<Vec<_>>();
+        lines.extend(
+            self.fields
+                .iter()
+                .map(|(key, value)| format!("{key}={value}")),
+        );
+        write_all(path, &lines.join("\n"))?;
+        Ok(())
+    }
+
+    /// The version of npm the project uses
+    pub fn npm(&self) -> Option<&str> {
+        self.fields.get("npm").map(String::as_str)
+    }
+
+    pub fn set_npm(&mut self, version: &str) {
+        self.fields.insert("npm".to_string(), version.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VersionFile;
+
+    #[test]
+    fn read_the_versions_of_the_tools() {
+        let file = VersionFile::parse("20.11.1\n");
+        assert_eq!(file.node.as_deref(), Some("20.11.1"));
+        assert_eq!(file.npm(), None);
+
+        let mut file = VersionFile::parse("\nnpm = 10.8.2\nlts/iron\n");
+        assert_eq!(file.node.as_deref(), Some("lts/iron"));
+        assert_eq!(file.npm(), Some("10.8.2"));
+
+        file.node = Some("20.11.1".to_string());
+        file.set_npm("10.9.0");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".nvmdrc");
+        file.write(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "20.11.1\nnpm=10.9.0"
+        );
+        assert_eq!(VersionFile::read(&path).unwrap(), file);
+    }
+}
@@ -0,0 +1,315 @@
+use super::INSTALLED_MARKER;
+use crate::module::{NodeVersionResolver, Setting};
+use anyhow::Result;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File, Metadata},
+    hash::{DefaultHasher, Hasher},
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+/// The result of deduplicating the installed versions
+#[derive(Debug, Default)]
+pub struct DedupeReport {
+    /// The number of files replaced with hard links
+    pub linked: usize,
+    /// The number of bytes saved
+    pub saved: u64,
+    /// The number of identical files that could not be linked across file systems
+    pub cross_device: usize,
+}
+
+struct FileEntry {
+    path: PathBuf,
+    device: u64,
+    id: Option<(u64, u64)>,
+}
+
+/// Hard-link byte-identical files across all installed versions
+pub fn dedupe(dry_run: bool) -> Result<DedupeReport> {
+    let directory = Setting::global()?.get_directory()?;
+    let dirs = NodeVersionResolver::installed()?
+        .into_iter()
+        .map(|version| directory.join(version.to_string()))
+        .collect::<Vec<_>>();
+
+    dedupe_dirs(&dirs, dry_run)
+}
+
+/// The size of the files under the path that are not shared with other versions,
+/// which is the space actually reclaimed when the path is removed
+pub fn reclaimable_size(path: &Path) -> u64 {
+    let mut size = 0;
+    let _ = walk(path, &mut |_, meta| {
+        if links(meta) <= 1 {
+            size += meta.len();
+        }
+    });
+    size
+}
+
+fn dedupe_dirs(dirs: &[PathBuf], dry_run: bool) -> Result<DedupeReport> {
+    // Only files with the same permissions, owner and size can be linked together,
+    // the ones on different devices are grouped too so they can be reported
+    let mut groups: HashMap<(u32, (u32, u32), u64), Vec<FileEntry>> = HashMap::new();
+    for dir in dirs {
+        walk(dir, &mut |path, meta| {
+            if meta.len() == 0 || is_metadata(path) {
+                return;
+            }
+            let (device, mode, owner) = file_key(meta);
+            let entries = groups.entry((mode, owner, meta.len())).or_default();
+            let id = file_id(meta);
+            // Files that are already linked together only need to be considered once
+            if id.is_none() || !entries.iter().any(|e| e.id == id) {
+                entries.push(FileEntry {
+                    path: path.to_path_buf(),
+                    device,
+                    id,
+                });
+            }
+        })?;
+    }
+
+    let mut report = DedupeReport::default();
+    for ((_, _, size), entries) in groups {
+        if entries.len() < 2 {
+            continue;
+        }
+
+        let mut by_hash: HashMap<u64, Vec<&FileEntry>> = HashMap::new();
+        for entry in &entries {
+            if let Ok(hash) = hash_file(&entry.path) {
+                by_hash.entry(hash).or_default().push(entry);
+            }
+        }
+
+        for same in by_hash.into_values().filter(|same| same.len() > 1) {
+            link_same(&same, size, dry_run, &mut report)?;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Link the files with the same hash to the first one on their device.
+/// Identical files left on other devices are only reported, hard links cannot cross file systems.
+fn link_same(
+    same: &[&FileEntry],
+    size: u64,
+    dry_run: bool,
+    report: &mut DedupeReport,
+) -> Result<()> {
+    let mut by_device: BTreeMap<u64, Vec<&FileEntry>> = BTreeMap::new();
+    for entry in same {
+        by_device.entry(entry.device).or_default().push(entry);
+    }
+
+    let canonicals = by_device
+        .values()
+        .map(|entries| entries[0])
+        .collect::<Vec<_>>();
+    for canonical in &canonicals[1..] {
+        if files_equal(&canonicals[0].path, &canonical.path)? {
+            report.cross_device += 1;
+        }
+    }
+
+    for entries in by_device.values() {
+        let canonical = entries[0];
+        for entry in &entries[1..] {
+            if !files_equal(&canonical.path, &entry.path)? {
+                continue;
+            }
+
+            if !dry_run {
+                match replace_with_link(&canonical.path, &entry.path) {
+                    Ok(_) => {}
+                    Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                        report.cross_device += 1;
+                        continue;
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+
+            report.linked += 1;
+            report.saved += size;
+        }
+    }
+
+    Ok(())
+}
+
+/// The files nvmd keeps for each version (e.g. its install time), they must stay separate
+fn is_metadata(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name == INSTALLED_MARKER || name.ends_with(".nvmd-dedupe"))
+}
+
+/// Recursively visit the regular files under the directory, symlinks are skipped
+fn walk(dir: &Path, visit: &mut dyn FnMut(&Path, &Metadata)) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let meta = fs::symlink_metadata(&path)?;
+        if meta.is_dir() {
+            walk(&path, visit)?;
+        } else if meta.is_file() {
+            visit(&path, &meta);
+        }
+    }
+    Ok(())
+}
+
+fn hash_file(path: &Path) -> io::Result<u64> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = DefaultHasher::new();
+    let mut buf = [0; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.write(&buf[..read]);
+    }
+    Ok(hasher.finish())
+}
+
+/// Compare the contents byte by byte, the hash alone is not collision resistant
+fn files_equal(a: &Path, b: &Path) -> io::Result<bool> {
+    let mut a = BufReader::new(File::open(a)?);
+    let mut b = BufReader::new(File::open(b)?);
+    let mut buf_a = [0; 64 * 1024];
+    let mut buf_b = [0; 64 * 1024];
+    loop {
+        let read = a.read(&mut buf_a)?;
+        if read == 0 {
+            return Ok(b.read(&mut buf_b)? == 0);
+        }
+        b.read_exact(&mut buf_b[..read])?;
+        if buf_a[..read] != buf_b[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+/// Replace the target with a hard link to the source.
+/// The link is created next to the target first, so the target is never left missing.
+/// This changes the mtime of the directory, the install time of a version is recorded separately.
+fn replace_with_link(source: &Path, target: &Path) -> io::Result<()> {
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    let temp = target.with_file_name(format!(".{}.nvmd-dedupe", file_name));
+    fs::hard_link(source, &temp)?;
+    fs::rename(&temp, target).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+/// The device, the permissions and the owner (uid and gid) of the file
+#[cfg(unix)]
+fn file_key(meta: &Metadata) -> (u64, u32, (u32, u32)) {
+    use std::os::unix::fs::MetadataExt;
+    (meta.dev(), meta.mode(), (meta.uid(), meta.gid()))
+}
+
+#[cfg(windows)]
+fn file_key(meta: &Metadata) -> (u64, u32, (u32, u32)) {
+    use std::os::windows::fs::MetadataExt;
+    (0, meta.file_attributes(), (0, 0))
+}
+
+#[cfg(unix)]
+fn file_id(meta: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(windows)]
+fn file_id(_meta: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(unix)]
+fn links(meta: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.nlink()
+}
+
+#[cfg(windows)]
+fn links(_meta: &Metadata) -> u64 {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DedupeReport, FileEntry, INSTALLED_MARKER, dedupe_dirs, link_same, reclaimable_size,
+    };
+    use std::fs;
+
+    #[test]
+    fn link_identical_files_across_versions() {
+        let root = tempfile::tempdir().unwrap();
+        let versions = ["20.10.0", "20.11.1"].map(|v| root.path().join(v));
+        for dir in &versions {
+            fs::create_dir_all(dir.join("include")).unwrap();
+            fs::write(dir.join("include").join("node.h"), "shared header").unwrap();
+        }
+        fs::write(versions[0].join("CHANGELOG.md"), "v20.10.0").unwrap();
+        fs::write(versions[1].join("CHANGELOG.md"), "v20.11.1").unwrap();
+
+        let report = dedupe_dirs(&versions, true).unwrap();
+        assert_eq!((report.linked, report.saved), (1, 13));
+
+        let report = dedupe_dirs(&versions, false).unwrap();
+        assert_eq!((report.linked, report.saved), (1, 13));
+        assert_eq!(
+            fs::read_to_string(versions[1].join("include").join("node.h")).unwrap(),
+            "shared header"
+        );
+
+        // Already linked files are not linked again
+        assert_eq!(dedupe_dirs(&versions, false).unwrap().linked, 0);
+
+        #[cfg(unix)]
+        assert_eq!(reclaimable_size(&versions[0]), 8);
+    }
+
+    #[test]
+    fn keep_the_install_markers_separate() {
+        let root = tempfile::tempdir().unwrap();
+        let versions = ["20.10.0", "20.11.1"].map(|v| root.path().join(v));
+        for dir in &versions {
+            fs::create_dir_all(dir).unwrap();
+            // installed in the same second
+            fs::write(dir.join(INSTALLED_MARKER), "1700000000").unwrap();
+        }
+
+        assert_eq!(dedupe_dirs(&versions, false).unwrap().linked, 0);
+        fs::write(versions[0].join(INSTALLED_MARKER), "1800000000").unwrap();
+        assert_eq!(
+            fs::read_to_string(versions[1].join(INSTALLED_MARKER)).unwrap(),
+            "1700000000"
+        );
+    }
+
+    #[test]
+    fn report_identical_files_on_other_devices() {
+        let root = tempfile::tempdir().unwrap();
+        let entries = [(1, "a"), (1, "b"), (2, "c")].map(|(device, name)| {
+            let path = root.path().join(name);
+            fs::write(&path, "shared").unwrap();
+            FileEntry {
+                path,
+                device,
+                id: None,
+            }
+        });
+
+        let mut report = DedupeReport::default();
+        link_same(&entries.each_ref(), 6, true, &mut report).unwrap();
+        assert_eq!((report.linked, report.cross_device), (1, 1));
+    }
+}
@@ -30,8 +30,7 @@ pub fn fetch(version: &Version) -> Result<()> {
         None => {
             let staging = create_staging_file(&install_dir)?;
             let remote_url = determine_remote_url(version)?;
-            let archive = fetch_remote_distro(version, &remote_url, staging.path())?;
-            archive
+            fetch_remote_distro(version, &remote_url, staging.path())?
         }
     };
 
@@ -71,6 +70,7 @@ at {:?}",
     })?;
 
     progress.finish_and_clear();
+    super::mark_installed(&dest)?;
 
     eprintln!(
         "{}",
@@ -88,7 +88,7 @@ fn fetch_remote_distro(
 ) -> Result<Box<dyn Archive>> {
     eprintln!("Downloading {} from {}", tool_version("node", version), url);
     archive::fetch_native(url, staging_path)
-        .with_context(|| format!("Could not download node@{} from {} \nPlease verify your internet connection and ensure the correct version is specified.", version.to_string(), url))
+        .with_context(|| format!("Could not download node@{} from {} \nPlease verify your internet connection and ensure the correct version is specified.", version, url))
 }
 
 /// Return the archive if it is valid. It may have been corrupted or interrupted in the middle of
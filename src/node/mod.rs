@@ -1,13 +1,21 @@
-use std::fmt::{self, Display};
-
-use anyhow::{Context, Result, anyhow};
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
 use cfg_if::cfg_if;
 use semver::Version;
 
 use crate::utils::help::node_available;
 
+mod dedupe;
 mod fetch;
 
+pub use dedupe::{dedupe, reclaimable_size};
+
 cfg_if! {
     if #[cfg(all(target_os = "windows", target_arch = "x86"))] {
         /// The OS component of a Node distro filename
@@ -16,8 +24,6 @@ cfg_if! {
         pub const NODE_DISTRO_ARCH: &str = "x86";
         /// The extension for Node distro files
         pub const NODE_DISTRO_EXTENSION: &str = "zip";
-        /// The file identifier in the Node index `files` array
-        pub const NODE_DISTRO_IDENTIFIER: &str = "win-x86-zip";
     } else if #[cfg(all(target_os = "windows", target_arch = "x86_64"))] {
         /// The OS component of a Node distro filename
         pub const NODE_DISTRO_OS: &str = "win";
@@ -25,8 +31,6 @@ cfg_if! {
         pub const NODE_DISTRO_ARCH: &str = "x64";
         /// The extension for Node distro files
         pub const NODE_DISTRO_EXTENSION: &str = "zip";
-        /// The file identifier in the Node index `files` array
-        pub const NODE_DISTRO_IDENTIFIER: &str = "win-x64-zip";
     } else if #[cfg(all(target_os = "windows", target_arch = "aarch64"))] {
         /// The OS component of a Node distro filename
         pub const NODE_DISTRO_OS: &str = "win";
@@ -34,16 +38,12 @@ cfg_if! {
         pub const NODE_DISTRO_ARCH: &str = "arm64";
         /// The extension for Node distro files
         pub const NODE_DISTRO_EXTENSION: &str = "zip";
-        /// The file identifier in the Node index `files` array
-        pub const NODE_DISTRO_IDENTIFIER: &str = "win-arm64-zip";
 
         // NOTE: Node support for pre-built ARM64 binaries on Windows was added in major version 20
         // For versions prior to that, we need to fall back on the x64 binaries via emulator
 
         /// The fallback architecture component of a Node distro filename
         pub const NODE_DISTRO_ARCH_FALLBACK: &str = "x64";
-        /// The fallback file identifier in the Node index `files` array
-        pub const NODE_DISTRO_IDENTIFIER_FALLBACK: &str = "win-x64-zip";
     } else if #[cfg(all(target_os = "macos", target_arch = "x86_64"))] {
         /// The OS component of a Node distro filename
         pub const NODE_DISTRO_OS: &str = "darwin";
@@ -51,8 +51,6 @@ cfg_if! {
         pub const NODE_DISTRO_ARCH: &str = "x64";
         /// The extension for Node distro files
         pub const NODE_DISTRO_EXTENSION: &str = "tar.gz";
-        /// The file identifier in the Node index `files` array
-        pub const NODE_DISTRO_IDENTIFIER: &str = "osx-x64-tar";
     } else if #[cfg(all(target_os = "macos", target_arch = "aarch64"))] {
         /// The OS component of a Node distro filename
         pub const NODE_DISTRO_OS: &str = "darwin";
@@ -60,16 +58,12 @@ cfg_if! {
         pub const NODE_DISTRO_ARCH: &str = "arm64";
         /// The extension for Node distro files
         pub const NODE_DISTRO_EXTENSION: &str = "tar.gz";
-        /// The file identifier in the Node index `files` array
-        pub const NODE_DISTRO_IDENTIFIER: &str = "osx-arm64-tar";
 
         // NOTE: Node support for pre-built Apple Silicon binaries was added in major version 16
         // For versions prior to that, we need to fall back on the x64 binaries via Rosetta 2
 
         /// The fallback architecture component of a Node distro filename
         pub const NODE_DISTRO_ARCH_FALLBACK: &str = "x64";
-        /// The fallback file identifier in the Node index `files` array
-        pub const NODE_DISTRO_IDENTIFIER_FALLBACK: &str = "osx-x64-tar";
     } else if #[cfg(all(target_os = "linux", target_arch = "x86_64"))] {
         /// The OS component of a Node distro filename
         pub const NODE_DISTRO_OS: &str = "linux";
@@ -77,8 +71,6 @@ cfg_if! {
         pub const NODE_DISTRO_ARCH: &str = "x64";
         /// The extension for Node distro files
         pub const NODE_DISTRO_EXTENSION: &str = "tar.gz";
-        /// The file identifier in the Node index `files` array
-        pub const NODE_DISTRO_IDENTIFIER: &str = "linux-x64";
     } else if #[cfg(all(target_os = "linux", target_arch = "aarch64"))] {
         /// The OS component of a Node distro filename
         pub const NODE_DISTRO_OS: &str = "linux";
@@ -86,8 +78,6 @@ cfg_if! {
         pub const NODE_DISTRO_ARCH: &str = "arm64";
         /// The extension for Node distro files
         pub const NODE_DISTRO_EXTENSION: &str = "tar.gz";
-        /// The file identifier in the Node index `files` array
-        pub const NODE_DISTRO_IDENTIFIER: &str = "linux-arm64";
     } else if #[cfg(all(target_os = "linux", target_arch = "arm"))] {
         /// The OS component of a Node distro filename
         pub const NODE_DISTRO_OS: &str = "linux";
@@ -95,8 +85,6 @@ cfg_if! {
         pub const NODE_DISTRO_ARCH: &str = "armv7l";
         /// The extension for Node distro files
         pub const NODE_DISTRO_EXTENSION: &str = "tar.gz";
-        /// The file identifier in the Node index `files` array
-        pub const NODE_DISTRO_IDENTIFIER: &str = "linux-armv7l";
     } else {
         compile_error!("Unsuppored operating system + architecture combination");
     }
@@ -159,7 +147,9 @@ impl Node {
         )
     }
 
-    pub fn ensure_fetched(&self) -> Result<()> {
+    /// Fetch the version if it has not been installed yet.
+    /// Returns whether the version was newly installed.
+    pub fn ensure_fetched(&self) -> Result<bool> {
         match node_available(&self.version.to_string())? {
             true => {
                 eprintln!(
@@ -170,13 +160,13 @@ impl Node {
                     ))
                     .yellow()
                 );
+                Ok(false)
             }
             false => {
                 fetch::fetch(&self.version)?;
+                Ok(true)
             }
-        };
-
-        Ok(())
+        }
     }
 }
 
@@ -186,6 +176,30 @@ impl Display for Node {
     }
 }
 
+/// The file recording when a version was installed. The mtime of the version directory
+/// is not used, it changes with 'nvmd dedupe' and global installs.
+const INSTALLED_MARKER: &str = ".nvmd-installed";
+
+/// Record the install time of the version installed into the directory
+fn mark_installed(dir: &Path) -> Result<()> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    fs::write(dir.join(INSTALLED_MARKER), now.as_secs().to_string())?;
+    Ok(())
+}
+
+/// The time the version in the directory was installed,
+/// the creation time of the directory for the versions installed before it was recorded
+pub fn installed_at(dir: &Path) -> Option<SystemTime> {
+    match fs::read_to_string(dir.join(INSTALLED_MARKER)) {
+        Ok(secs) => secs
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+        Err(_) => dir.metadata().and_then(|meta| meta.created()).ok(),
+    }
+}
+
 pub fn tool_version<N, V>(name: N, version: V) -> String
 where
     N: std::fmt::Display + Sized,
@@ -193,3 +207,21 @@ where
 {
     format!("{:}@{:}", name, version)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{installed_at, mark_installed};
+    use std::{fs, time::SystemTime};
+
+    #[test]
+    fn installed_at_is_not_changed_by_the_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        mark_installed(dir.path()).unwrap();
+        let installed = installed_at(dir.path()).unwrap();
+        assert!(installed <= SystemTime::now());
+
+        fs::write(dir.path().join("marker.tmp"), "").unwrap();
+        fs::remove_file(dir.path().join("marker.tmp")).unwrap();
+        assert_eq!(installed_at(dir.path()), Some(installed));
+    }
+}
@@ -0,0 +1,659 @@
+use std::ffi::OsStr;
+
+/// The npm options that take a value, modelled on the option definitions of npm.
+/// https://docs.npmjs.com/cli/v10/using-npm/config
+const VALUE_OPTIONS: [&str; 91] = [
+    "_auth",
+    "access",
+    "also",
+    "audit-level",
+    "auth-type",
+    "before",
+    "ca",
+    "cache",
+    "cache-max",
+    "cache-min",
+    "cafile",
+    "call",
+    "cert",
+    "ci-name",
+    "cidr",
+    "cpu",
+    "depth",
+    "diff",
+    "diff-dst-prefix",
+    "diff-src-prefix",
+    "diff-unified",
+    "editor",
+    "expect-result-count",
+    "fetch-retries",
+    "fetch-retry-factor",
+    "fetch-retry-maxtimeout",
+    "fetch-retry-mintimeout",
+    "fetch-timeout",
+    "git",
+    "globalconfig",
+    "heading",
+    "https-proxy",
+    "include",
+    "init-author-email",
+    "init-author-name",
+    "init-author-url",
+    "init-license",
+    "init-module",
+    "init-version",
+    "init.author.email",
+    "init.author.name",
+    "init.author.url",
+    "init.license",
+    "init.module",
+    "init.version",
+    "install-strategy",
+    "key",
+    "libc",
+    "local-address",
+    "location",
+    "lockfile-version",
+    "loglevel",
+    "logs-dir",
+    "logs-max",
+    "maxsockets",
+    "message",
+    "node-gyp",
+    "node-options",
+    "node-version",
+    "noproxy",
+    "npm-version",
+    "omit",
+    "only",
+    "os",
+    "otp",
+    "pack-destination",
+    "package",
+    "prefix",
+    "preid",
+    "provenance-file",
+    "proxy",
+    "registry",
+    "replace-registry-host",
+    "save-prefix",
+    "sbom-format",
+    "sbom-type",
+    "scope",
+    "script-shell",
+    "searchexclude",
+    "searchlimit",
+    "searchopts",
+    "searchstaleness",
+    "shell",
+    "tag",
+    "tag-version-prefix",
+    "umask",
+    "user-agent",
+    "userconfig",
+    "viewer",
+    "which",
+    "workspace",
+];
+
+/// The boolean options of npm, only needed to tell the abbreviations apart.
+/// The unknown options are boolean ones too, as npm treats them.
+const BOOLEAN_OPTIONS: [&str; 68] = [
+    "all",
+    "allow-same-version",
+    "audit",
+    "bin-links",
+    "browser",
+    "color",
+    "commit-hooks",
+    "description",
+    "dev",
+    "diff-ignore-all-space",
+    "diff-name-only",
+    "diff-no-prefix",
+    "diff-text",
+    "dry-run",
+    "engine-strict",
+    "expect-results",
+    "force",
+    "foreground-scripts",
+    "format-package-lock",
+    "fund",
+    "git-tag-version",
+    "global",
+    "global-style",
+    "if-present",
+    "ignore-scripts",
+    "include-staged",
+    "include-workspace-root",
+    "install-links",
+    "json",
+    "legacy-bundling",
+    "legacy-peer-deps",
+    "link",
+    "long",
+    "offline",
+    "omit-lockfile-deps",
+    "optional",
+    "package-lock",
+    "package-lock-only",
+    "parseable",
+    "prefer-dedupe",
+    "prefer-offline",
+    "prefer-online",
+    "production",
+    "progress",
+    "provenance",
+    "read-only",
+    "rebuild-bundle",
+    "save",
+    "save-bundle",
+    "save-dev",
+    "save-exact",
+    "save-optional",
+    "save-peer",
+    "save-prod",
+    "shrinkwrap",
+    "sign-git-commit",
+    "sign-git-tag",
+    "strict-peer-deps",
+    "strict-ssl",
+    "timing",
+    "unicode",
+    "update-notifier",
+    "usage",
+    "version",
+    "versions",
+    "workspaces",
+    "workspaces-update",
+    "yes",
+];
+
+/// The shorthands of npm and the options they expand to
+const SHORTHANDS: [(&str, &[&str]); 37] = [
+    ("B", &["--save-bundle"]),
+    ("C", &["--prefix"]),
+    ("D", &["--save-dev"]),
+    ("E", &["--save-exact"]),
+    ("H", &["--usage"]),
+    ("O", &["--save-optional"]),
+    ("P", &["--save-prod"]),
+    ("S", &["--save"]),
+    ("a", &["--all"]),
+    ("c", &["--call"]),
+    ("d", &["--loglevel", "info"]),
+    ("dd", &["--loglevel", "verbose"]),
+    ("ddd", &["--loglevel", "silly"]),
+    ("desc", &["--description"]),
+    ("enjoy-by", &["--before"]),
+    ("f", &["--force"]),
+    ("g", &["--global"]),
+    ("h", &["--usage"]),
+    ("help", &["--usage"]),
+    ("iwr", &["--include-workspace-root"]),
+    ("l", &["--long"]),
+    ("local", &["--no-global"]),
+    ("m", &["--message"]),
+    ("n", &["--no-yes"]),
+    ("no", &["--no-yes"]),
+    ("p", &["--parseable"]),
+    ("porcelain", &["--parseable"]),
+    ("q", &["--loglevel", "warn"]),
+    ("quiet", &["--loglevel", "warn"]),
+    ("readonly", &["--read-only"]),
+    ("reg", &["--registry"]),
+    ("s", &["--loglevel", "silent"]),
+    ("silent", &["--loglevel", "silent"]),
+    ("v", &["--version"]),
+    ("verbose", &["--loglevel", "verbose"]),
+    ("w", &["--workspace"]),
+    ("ws", &["--workspaces"]),
+];
+
+/// The commands of npm, the ones its aliases and abbreviations resolve to
+const COMMANDS: [&str; 67] = [
+    "access",
+    "adduser",
+    "audit",
+    "bugs",
+    "cache",
+    "ci",
+    "completion",
+    "config",
+    "dedupe",
+    "deprecate",
+    "diff",
+    "dist-tag",
+    "docs",
+    "doctor",
+    "edit",
+    "exec",
+    "explain",
+    "explore",
+    "find-dupes",
+    "fund",
+    "get",
+    "help",
+    "help-search",
+    "init",
+    "install",
+    "install-ci-test",
+    "install-test",
+    "link",
+    "ll",
+    "login",
+    "logout",
+    "ls",
+    "org",
+    "outdated",
+    "owner",
+    "pack",
+    "ping",
+    "pkg",
+    "prefix",
+    "profile",
+    "prune",
+    "publish",
+    "query",
+    "rebuild",
+    "repo",
+    "restart",
+    "root",
+    "run-script",
+    "sbom",
+    "search",
+    "set",
+    "shrinkwrap",
+    "star",
+    "stars",
+    "start",
+    "stop",
+    "team",
+    "test",
+    "token",
+    "undeprecate",
+    "uninstall",
+    "unpublish",
+    "unstar",
+    "update",
+    "version",
+    "view",
+    "whoami",
+];
+
+/// The aliases of the commands, as npm lists them (typos included).
+/// npm spells out the prefixes of 'install' itself, they are ambiguous with 'install-test'.
+const ALIASES: [(&str, &str); 56] = [
+    ("author", "owner"),
+    ("home", "docs"),
+    ("issues", "bugs"),
+    ("info", "view"),
+    ("show", "view"),
+    ("find", "search"),
+    ("add", "install"),
+    ("unlink", "uninstall"),
+    ("remove", "uninstall"),
+    ("rm", "uninstall"),
+    ("r", "uninstall"),
+    ("un", "uninstall"),
+    ("rb", "rebuild"),
+    ("list", "ls"),
+    ("ln", "link"),
+    ("create", "init"),
+    ("i", "install"),
+    ("it", "install-test"),
+    ("cit", "install-ci-test"),
+    ("up", "update"),
+    ("c", "config"),
+    ("s", "search"),
+    ("se", "search"),
+    ("tst", "test"),
+    ("t", "test"),
+    ("ddp", "dedupe"),
+    ("v", "view"),
+    ("run", "run-script"),
+    ("clean-install", "ci"),
+    ("clean-install-test", "install-ci-test"),
+    ("x", "exec"),
+    ("why", "explain"),
+    ("la", "ll"),
+    ("verison", "version"),
+    ("ic", "ci"),
+    ("innit", "init"),
+    ("in", "install"),
+    ("ins", "install"),
+    ("inst", "install"),
+    ("insta", "install"),
+    ("instal", "install"),
+    ("isnt", "install"),
+    ("isnta", "install"),
+    ("isntal", "install"),
+    ("isntall", "install"),
+    ("install-clean", "ci"),
+    ("isntall-clean", "ci"),
+    ("hlep", "help"),
+    ("dist-tags", "dist-tag"),
+    ("upgrade", "update"),
+    ("udpate", "update"),
+    ("rum", "run-script"),
+    ("sit", "install-ci-test"),
+    ("urn", "run-script"),
+    ("ogr", "org"),
+    ("add-user", "adduser"),
+];
+
+/// The commands that install, update or remove packages
+const CHANGING_COMMANDS: [&str; 8] = [
+    "ci",
+    "dedupe",
+    "install",
+    "install-ci-test",
+    "install-test",
+    "link",
+    "uninstall",
+    "update",
+];
+
+/// The arguments of an npm command, classified the way npm parses them
+#[derive(Debug, Default)]
+pub struct NpmArgs<'a> {
+    /// The command and its operands
+    pub positionals: Vec<&'a OsStr>,
+    /// The options, with the values of the options that take one
+    pub flags: Vec<&'a OsStr>,
+    /// Whether the command applies to the global packages
+    pub global: bool,
+}
+
+impl<'a> NpmArgs<'a> {
+    pub fn parse<S>(args: &'a [S]) -> Self
+    where
+        S: AsRef<OsStr>,
+    {
+        let mut parsed = Self::default();
+        let mut args = args.iter().map(AsRef::as_ref).peekable();
+        while let Some(arg) = args.next() {
+            let Some(flag) = arg
+                .to_str()
+                .filter(|arg| arg.starts_with('-') && *arg != "-")
+            else {
+                parsed.positionals.push(arg);
+                continue;
+            };
+
+            // everything after '--' is an operand
+            if flag == "--" {
+                parsed.positionals.extend(args.by_ref());
+                break;
+            }
+
+            parsed.flags.push(arg);
+            let (flag, inline) = match flag.split_once('=') {
+                Some((flag, value)) => (flag, Some(value)),
+                None => (flag, None),
+            };
+
+            // a shorthand may expand to several options, or to an option and its value
+            let mut options = expand_shorthand(flag).into_iter().peekable();
+            while let Some(option) = options.next() {
+                let (name, negated) = resolve_option(option);
+                let value = match options.next_if(|next| !next.starts_with('-')) {
+                    Some(value) => Some(value),
+                    None if options.peek().is_some() => None,
+                    None => match inline {
+                        Some(value) => Some(value),
+                        None => {
+                            let next = args.next_if(|next| takes_value(name, negated, next));
+                            parsed.flags.extend(next);
+                            next.and_then(OsStr::to_str)
+                        }
+                    },
+                };
+
+                match name {
+                    "global" => parsed.global = (value != Some("false")) != negated,
+                    "location" => parsed.global = value == Some("global"),
+                    _ => {}
+                }
+            }
+        }
+        parsed
+    }
+
+    /// The npm command, with its alias or abbreviation resolved (e.g. 'i' or 'isntall' to 'install')
+    pub fn command(&self) -> Option<&'static str> {
+        resolve_command(self.positionals.first()?.to_str()?)
+    }
+
+    /// Whether the command installs, updates or removes packages
+    pub fn changes_packages(&self) -> bool {
+        self.command()
+            .is_some_and(|command| CHANGING_COMMANDS.contains(&command))
+    }
+}
+
+/// Resolve a command the way npm does: a command, an alias, or an unambiguous abbreviation
+/// of either (e.g. 'uninst' or 'install-cl', which is then an alias of 'ci')
+fn resolve_command(cmd: &str) -> Option<&'static str> {
+    let alias = |name: &str| ALIASES.iter().find(|(alias, _)| *alias == name);
+    if let Some(command) = COMMANDS.iter().find(|command| **command == cmd) {
+        return Some(command);
+    }
+    if let Some((_, command)) = alias(cmd) {
+        return Some(command);
+    }
+
+    let names = COMMANDS
+        .iter()
+        .chain(ALIASES.iter().map(|(alias, _)| alias));
+    let name = abbreviation(cmd, names)?;
+    Some(alias(name).map_or(name, |(_, command)| command))
+}
+
+/// Expand the shorthands the way nopt does, single letters may be combined (e.g. '-gD').
+/// The options that are not shorthands are returned as they are.
+fn expand_shorthand(flag: &str) -> Vec<&str> {
+    let key = flag.trim_start_matches('-');
+    let shorthand = |key: &str| {
+        SHORTHANDS
+            .iter()
+            .find(|(short, _)| *short == key)
+            .map(|(_, options)| options.to_vec())
+    };
+
+    if is_option(key) {
+        return vec![flag];
+    }
+    if let Some(options) = shorthand(key) {
+        return options;
+    }
+    let singles = key
+        .char_indices()
+        .map(|(i, c)| shorthand(&key[i..i + c.len_utf8()]))
+        .collect::<Option<Vec<_>>>();
+    if !key.is_empty()
+        && let Some(singles) = singles
+    {
+        return singles.concat();
+    }
+    if abbreviation(key, VALUE_OPTIONS.iter().chain(&BOOLEAN_OPTIONS)).is_some() {
+        return vec![flag];
+    }
+    abbreviation(key, SHORTHANDS.iter().map(|(short, _)| short))
+        .and_then(shorthand)
+        .unwrap_or_else(|| vec![flag])
+}
+
+/// The name of the option without its dashes and 'no-' prefixes,
+/// and whether it is negated. The unambiguous abbreviations are expanded (e.g. '--regis').
+fn resolve_option(option: &str) -> (&str, bool) {
+    let mut name = option.trim_start_matches('-');
+    let mut negated = false;
+    while let Some(rest) = name.strip_prefix("no-") {
+        negated = !negated;
+        name = rest;
+    }
+    if is_option(name) {
+        return (name, negated);
+    }
+    let name = abbreviation(name, VALUE_OPTIONS.iter().chain(&BOOLEAN_OPTIONS)).unwrap_or(name);
+    (name, negated)
+}
+
+fn is_option(name: &str) -> bool {
+    VALUE_OPTIONS.contains(&name) || BOOLEAN_OPTIONS.contains(&name)
+}
+
+/// The only name that starts with the prefix
+fn abbreviation<'n>(prefix: &str, names: impl Iterator<Item = &'n &'n str>) -> Option<&'n str> {
+    let mut names = names.filter(|name| name.starts_with(prefix));
+    match (names.next(), names.next()) {
+        (Some(name), None) if !prefix.is_empty() => Some(name),
+        _ => None,
+    }
+}
+
+/// Whether the argument after the option is its value
+fn takes_value(name: &str, negated: bool, next: &OsStr) -> bool {
+    if negated {
+        return next == "true" || next == "false";
+    }
+    if VALUE_OPTIONS.contains(&name) {
+        return next != "--";
+    }
+    match name {
+        // the boolean options that also accept a string
+        "browser" => !next.to_str().is_some_and(|next| next.starts_with('-')),
+        "color" => next == "always" || next == "true" || next == "false",
+        _ => next == "true" || next == "false",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NpmArgs;
+
+    fn parse(args: &str) -> NpmArgs<'_> {
+        let args = args.split_whitespace().collect::<Vec<_>>();
+        // the arguments only need to live as long as the test
+        NpmArgs::parse(args.leak())
+    }
+
+    #[test]
+    fn detect_global_commands() {
+        for args in [
+            "install -g typescript",
+            "i --global typescript",
+            "install --global=true typescript",
+            "install --global true typescript",
+            "install --location=global typescript",
+            "install --location global typescript",
+            "--location=user install typescript -g",
+        ] {
+            assert!(parse(args).global, "{args}");
+        }
+
+        for args in [
+            "install typescript",
+            "install --global=false typescript",
+            "install --global false typescript",
+            "install -g --no-global typescript",
+            "install -g --location=project typescript",
+            "install --location user typescript",
+            "run build -- -g",
+        ] {
+            assert!(!parse(args).global, "{args}");
+        }
+    }
+
+    #[test]
+    fn skip_the_values_of_options() {
+        let args = parse("-w app --prefix /opt/npm install --registry https://r.io -C /x eslint");
+        assert_eq!(args.command().unwrap(), "install");
+        assert_eq!(args.positionals, ["install", "eslint"]);
+        assert_eq!(
+            args.flags,
+            [
+                "-w",
+                "app",
+                "--prefix",
+                "/opt/npm",
+                "--registry",
+                "https://r.io",
+                "-C",
+                "/x"
+            ]
+        );
+
+        let args = parse("--workspace=app link --save-dev ../lib");
+        assert_eq!(args.positionals, ["link", "../lib"]);
+        assert_eq!(args.flags, ["--workspace=app", "--save-dev"]);
+    }
+
+    #[test]
+    fn skip_the_values_of_all_options() {
+        for args in [
+            "exec --package foo -g",
+            "--node-options --inspect install -g",
+            "--user-agent custom install -g",
+            "--pack-destination /tmp install -g",
+            "--noproxy example.com install -g",
+            "--git /usr/bin/git install -g",
+            "--tag-version-prefix v install -g",
+            "--umask 022 install -g",
+            "--searchlimit 10 install -g",
+            "--browser firefox install -g",
+            "--color always install -g",
+        ] {
+            let args_ = parse(args);
+            assert!(args_.global, "{args}");
+            assert_eq!(args_.positionals.len(), 1, "{args}");
+        }
+    }
+
+    #[test]
+    fn expand_shorthands_and_abbreviations() {
+        let args = parse("--reg https://r.io --prefi /opt/npm install -gD eslint");
+        assert_eq!(args.positionals, ["install", "eslint"]);
+        assert!(args.global);
+
+        let args = parse("--locat global -dd install eslint");
+        assert_eq!(args.positionals, ["install", "eslint"]);
+        assert!(args.global);
+
+        assert!(!parse("install -g --local eslint").global);
+        assert!(!parse("install --glob --no-glob eslint").global);
+        assert!(parse("install --no-global false eslint").global);
+        // ambiguous, a boolean option like any unknown one
+        assert_eq!(parse("--sav install").positionals, ["install"]);
+    }
+
+    #[test]
+    fn operands_after_the_separator() {
+        let args = parse("exec -g -- eslint --global");
+        assert!(args.global);
+        assert_eq!(args.positionals, ["exec", "eslint", "--global"]);
+        assert_eq!(args.flags, ["-g"]);
+        assert_eq!(parse("--loglevel").command(), None);
+    }
+
+    #[test]
+    fn resolve_aliases_and_abbreviations_of_commands() {
+        for (cmd, command) in [
+            ("install", Some("install")),
+            ("isntall", Some("install")),
+            ("inst", Some("install")),
+            ("install-t", Some("install-test")),
+            ("install-cl", Some("ci")),
+            ("uninst", Some("uninstall")),
+            ("udpate", Some("update")),
+            ("run", Some("run-script")),
+            ("ru", None),
+            ("eslint", None),
+        ] {
+            assert_eq!(parse(cmd).command(), command, "{cmd}");
+        }
+        assert!(parse("ddp").changes_packages());
+        assert!(!parse("outdated").changes_packages());
+    }
+}
@@ -1,13 +1,52 @@
 use crate::{module::Context, utils::command};
-use anyhow::{anyhow, bail, Context as AnyhowContext, Result};
+use anyhow::{Context as AnyhowContext, Result, anyhow, bail};
 use std::{
+    env,
     ffi::OsStr,
+    fs,
     path::{Path, PathBuf},
     process::Stdio,
 };
 
-pub(super) fn get_npm_prefix() -> Result<PathBuf> {
-    let path = Context::global()?.env_path()?;
+/// Find the global 'node_modules' of the version of the context, the same as `npm root -g`.
+///
+/// The global prefix is resolved the way npm does it, from the highest precedence:
+/// the '--prefix' flag, the 'npm_config_prefix' environment variable, the user '.npmrc',
+/// the global 'npmrc' of the Node installation, and finally the installation itself.
+/// `npm root -g` is only spawned when that fails.
+pub(super) fn get_npm_prefix(context: &Context, flags: &[&OsStr]) -> Result<PathBuf> {
+    if let Some(root) = native_npm_root(context, flags) {
+        return Ok(root);
+    }
+
+    npm_root_global(context)
+}
+
+/// The directory npm links the bins of the global packages into, for their 'node_modules'
+pub(super) fn global_bin_dir(root: &Path) -> Option<PathBuf> {
+    match cfg!(windows) {
+        true => root.parent().map(Path::to_path_buf),
+        false => Some(root.parent()?.parent()?.join("bin")),
+    }
+}
+
+fn native_npm_root(context: &Context, flags: &[&OsStr]) -> Option<PathBuf> {
+    let install_dir = context.install_dir().ok()?;
+    let prefix = prefix_from_flags(flags)
+        .or_else(prefix_from_env)
+        .or_else(|| prefix_from_npmrc(&user_npmrc()?))
+        .or_else(|| prefix_from_npmrc(&global_npmrc(&install_dir)))
+        .unwrap_or(install_dir);
+
+    let root = match cfg!(windows) {
+        true => prefix.join("node_modules"),
+        false => prefix.join("lib").join("node_modules"),
+    };
+    root.is_dir().then_some(root)
+}
+
+fn npm_root_global(context: &Context) -> Result<PathBuf> {
+    let path = context.env_path()?;
     let mut command = command::create_command("npm");
 
     let output = command
@@ -28,25 +67,171 @@ pub(super) fn get_npm_prefix() -> Result<PathBuf> {
     bail!("No valid npm prefix found");
 }
 
-pub(super) fn is_global<A>(args: &[A]) -> bool
-where
-    A: AsRef<OsStr>,
-{
-    args.iter()
-        .fold(false, |global, arg| match arg.as_ref().to_str() {
-            Some("-g") | Some("--global") => true,
-            _ => global,
+fn prefix_from_flags(flags: &[&OsStr]) -> Option<PathBuf> {
+    // the last of '--prefix' and its shorthand '-C' wins
+    let mut prefix = None;
+    let mut flags = flags.iter().filter_map(|flag| flag.to_str());
+    while let Some(flag) = flags.next() {
+        match flag.split_once('=') {
+            Some(("--prefix" | "-C", value)) => prefix = Some(value),
+            None if flag == "--prefix" || flag == "-C" => prefix = flags.next(),
+            _ => {}
+        }
+    }
+    prefix.and_then(expand_path)
+}
+
+fn prefix_from_env() -> Option<PathBuf> {
+    ["npm_config_prefix", "NPM_CONFIG_PREFIX"]
+        .iter()
+        .filter_map(|key| env::var(key).ok())
+        .find(|value| !value.trim().is_empty())
+        .and_then(|value| expand_path(&value))
+}
+
+fn user_npmrc() -> Option<PathBuf> {
+    ["npm_config_userconfig", "NPM_CONFIG_USERCONFIG"]
+        .iter()
+        .filter_map(|key| env::var(key).ok())
+        .find(|value| !value.trim().is_empty())
+        .and_then(|value| expand_path(&value))
+        .or_else(|| dirs::home_dir().map(|home| home.join(".npmrc")))
+}
+
+fn global_npmrc(install_dir: &Path) -> PathBuf {
+    ["npm_config_globalconfig", "NPM_CONFIG_GLOBALCONFIG"]
+        .iter()
+        .filter_map(|key| env::var(key).ok())
+        .find(|value| !value.trim().is_empty())
+        .and_then(|value| expand_path(&value))
+        .unwrap_or_else(|| install_dir.join("etc").join("npmrc"))
+}
+
+fn prefix_from_npmrc(path: &Path) -> Option<PathBuf> {
+    parse_npmrc_prefix(&fs::read_to_string(path).ok()?).and_then(|value| expand_path(&value))
+}
+
+/// Read the 'prefix' key of an ini formatted npmrc, the last one wins
+fn parse_npmrc_prefix(content: &str) -> Option<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with(';') && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .filter(|(key, _)| key.trim() == "prefix")
+        .map(|(_, value)| {
+            value
+                .trim()
+                .trim_matches('"')
+                .trim_matches('\'')
+                .to_string()
         })
+        .rfind(|value| !value.is_empty())
+}
+
+/// Expand '${VAR}' references and a leading '~' like npm does.
+/// Returns None if a referenced variable is not set.
+fn expand_path(value: &str) -> Option<PathBuf> {
+    let mut expanded = String::new();
+    let mut rest = value.trim();
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..].find('}')? + start;
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&env::var(&rest[start + 2..end]).ok()?);
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    match expanded.strip_prefix("~/").or(expanded.strip_prefix("~\\")) {
+        Some(path) => dirs::home_dir().map(|home| home.join(path)),
+        None => Some(PathBuf::from(expanded)),
+    }
 }
 
-pub(super) fn is_relative_path<A>(arg: &A) -> bool
-where
-    A: AsRef<OsStr>,
-{
-    if let Some(arg_str) = arg.as_ref().to_str() {
-        let path = Path::new(arg_str);
-        path.is_relative() && (path.starts_with(".") || path.starts_with(".."))
-    } else {
-        false
+/// Packages that are bundled with Node.js and never managed as global packages
+const BUNDLED_PACKAGES: [&str; 2] = ["npm", "corepack"];
+
+/// List the top-level packages installed in the global 'node_modules', skipping the bundled ones.
+/// The ones linked via 'npm link' are only listed when `links` is set.
+pub fn global_packages(root: &Path, links: bool) -> Result<Vec<String>> {
+    let is_package = |entry: &fs::DirEntry| -> Result<bool> {
+        let file_type = entry.file_type()?;
+        Ok(file_type.is_dir() || (links && file_type.is_symlink()))
+    };
+
+    let mut pkgs = vec![];
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') || BUNDLED_PACKAGES.contains(&name.as_str()) {
+            continue;
+        }
+
+        if name.starts_with('@') && entry.file_type()?.is_dir() {
+            for scoped in fs::read_dir(entry.path())? {
+                let scoped = scoped?;
+                if is_package(&scoped)? {
+                    pkgs.push(format!("{}/{}", name, scoped.file_name().to_string_lossy()));
+                }
+            }
+        } else if is_package(&entry)? {
+            pkgs.push(name);
+        }
+    }
+    pkgs.sort();
+    Ok(pkgs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_path, global_bin_dir, parse_npmrc_prefix, prefix_from_flags};
+    use std::{ffi::OsStr, path::PathBuf};
+
+    #[test]
+    fn read_prefix_from_npmrc() {
+        let content = "; comment\nregistry=https://registry.npmjs.org/\nprefix = /opt/npm\n# prefix=/ignored\n";
+        assert_eq!(parse_npmrc_prefix(content), Some("/opt/npm".to_string()));
+        assert_eq!(
+            parse_npmrc_prefix("prefix=\"/a\"\nprefix=/b"),
+            Some("/b".to_string())
+        );
+        assert_eq!(
+            parse_npmrc_prefix("registry=https://registry.npmjs.org/"),
+            None
+        );
+    }
+
+    #[test]
+    fn expand_variables_in_paths() {
+        let path = std::env::var("PATH").unwrap();
+        assert_eq!(
+            expand_path("${PATH}/npm"),
+            Some(PathBuf::from(format!("{path}/npm")))
+        );
+        assert_eq!(expand_path("${NVMD_SURELY_UNSET_VARIABLE}/npm"), None);
+        assert_eq!(expand_path("/opt/npm"), Some(PathBuf::from("/opt/npm")));
+    }
+
+    #[test]
+    fn find_the_bin_dir_of_a_global_prefix() {
+        let prefix = PathBuf::from("/opt/npm");
+        let root = match cfg!(windows) {
+            true => prefix.join("node_modules"),
+            false => prefix.join("lib").join("node_modules"),
+        };
+        let bin_dir = match cfg!(windows) {
+            true => prefix,
+            false => prefix.join("bin"),
+        };
+        assert_eq!(global_bin_dir(&root), Some(bin_dir));
+    }
+
+    #[test]
+    fn read_prefix_from_flags() {
+        let flags = [OsStr::new("-g"), OsStr::new("--prefix=/opt/npm")];
+        assert_eq!(prefix_from_flags(&flags), Some(PathBuf::from("/opt/npm")));
+        let flags = ["--prefix", "/opt/npm", "-C", "/opt/other"].map(OsStr::new);
+        assert_eq!(prefix_from_flags(&flags), Some(PathBuf::from("/opt/other")));
+        assert_eq!(prefix_from_flags(&[OsStr::new("-g")]), None);
     }
 }
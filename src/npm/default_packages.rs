@@ -0,0 +1,82 @@
+use super::install;
+use crate::module::{Context, Setting, nvmd_home};
+use anyhow::Result;
+use fs_extra::file::read_to_string;
+
+/// Install the default packages into a newly installed version.
+/// They are listed in '$NVMD_HOME/default-packages' (one spec per line, '#' for comments)
+/// and in the 'default_packages' setting.
+pub fn install_default_packages(version: &str) -> Result<()> {
+    let specs = default_packages()?;
+    if specs.is_empty() {
+        return Ok(());
+    }
+
+    eprintln!(
+        "Installing default packages into Node@v{}: {}",
+        version,
+        specs.join(", ")
+    );
+    install::install_global(&Context::from_version(version.to_string()), &specs)
+}
+
+fn default_packages() -> Result<Vec<String>> {
+    let mut specs = vec![];
+
+    let path = nvmd_home()?.default_packages_path();
+    if path.is_file() {
+        specs.extend(parse_default_packages(&read_to_string(&path)?));
+    }
+
+    for spec in Setting::global()?.get_default_packages() {
+        if !specs.contains(&spec) {
+            specs.push(spec);
+        }
+    }
+
+    Ok(specs)
+}
+
+/// A comment starts a line or follows a whitespace,
+/// a '#' within a spec is part of it (e.g. 'user/repo#semver:^1')
+fn parse_default_packages(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            let end = line
+                .char_indices()
+                .find(|(i, c)| *c == '#' && (*i == 0 || line[..*i].ends_with(char::is_whitespace)))
+                .map_or(line.len(), |(i, _)| i);
+            line[..end].trim()
+        })
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_default_packages;
+
+    #[test]
+    fn parse_specs_skipping_comments_and_blank_lines() {
+        let content = "# tools\ntypescript\n\n  pnpm@9 # package manager\n@vue/cli\n";
+        assert_eq!(
+            parse_default_packages(content),
+            vec!["typescript", "pnpm@9", "@vue/cli"]
+        );
+    }
+
+    #[test]
+    fn keep_the_git_refs_of_specs() {
+        let content = "user/repo#semver:^1\ngit+https://example.com/repo.git#v1.2.0 # pinned\n";
+        assert_eq!(
+            parse_default_packages(content),
+            vec![
+                "user/repo#semver:^1",
+                "git+https://example.com/repo.git#v1.2.0"
+            ]
+        );
+    }
+}
@@ -1,52 +1,27 @@
-use super::common;
-use crate::{
-    module::{Context, PackageJson, Packages},
-    utils::help::link_package,
-};
-use anyhow::Result;
-use regex::Regex;
+use super::snapshot::Snapshot;
+use crate::{module::Context, utils::command};
+use anyhow::{Result, bail};
 use std::ffi::OsStr;
 
-/// The arguments passed to a global install command
-pub struct InstallArgs<'a> {
-    /// Common arguments that apply to each tool (e.g. flags)
-    pub common_args: Vec<&'a OsStr>,
-    /// The individual tool arguments
-    pub tools: Vec<&'a OsStr>,
-}
-
-impl InstallArgs<'_> {
-    /// Convert these global install arguments into an executor for the command
-    pub fn after_executor(&self) -> Result<()> {
-        let version = Context::global()?.get_version().unwrap_or_default();
-        let reg = Regex::new("@[0-9]|@latest|@\"|@npm:")?;
-        let npm_prefix = common::get_npm_prefix()?;
-
-        let mut pkg_names = vec![];
-        self.tools
-            .iter()
-            .map(|tool| {
-                let pkg = tool.to_string_lossy().to_string();
-                match reg.find(&pkg) {
-                    Some(mat) => pkg[0..(mat.start())].to_string(),
-                    None => pkg,
-                }
-            })
-            .for_each(|pkg| {
-                let names = PackageJson::new(&npm_prefix, &pkg).bin_names();
-                if !names.is_empty() {
-                    pkg_names.extend(names.iter().cloned());
-                }
-            });
+/// Install the packages globally with the npm of the version of the context,
+/// then record the changes to the global packages and update the shims
+pub(super) fn install_global<S>(context: &Context, specs: &[S]) -> Result<()>
+where
+    S: AsRef<OsStr>,
+{
+    let snapshot = Snapshot::take(context, &[]);
 
-        let mut packages = Packages::new()?;
-        packages.record_installed(&pkg_names, &version);
-        packages.save()?;
+    let mut command = command::create_command("npm");
+    command.args(["install", "--global"]).args(specs);
+    context.apply_env(&mut command)?;
 
-        for name in &pkg_names {
-            link_package(name)?;
-        }
-
-        Ok(())
+    let status = command.status()?;
+    if !status.success() {
+        bail!(
+            "Failed to install global packages into Node@v{}",
+            context.get_version().unwrap_or_default()
+        );
     }
+
+    snapshot.reconcile(context, &[])
 }
@@ -0,0 +1,25 @@
+use super::{common, install};
+use crate::module::Context;
+use anyhow::Result;
+
+/// Reinstall the top-level global packages of one installed version into another.
+/// Like nvm, the packages are installed by name, so the target gets their latest releases.
+pub fn reinstall_packages(from: &str, to: &str) -> Result<()> {
+    let source = Context::from_version(from.to_string());
+    let target = Context::from_version(to.to_string());
+
+    let pkgs = common::global_packages(&common::get_npm_prefix(&source, &[])?, false)?;
+    if pkgs.is_empty() {
+        eprintln!("No global packages found under Node@v{}", from);
+        return Ok(());
+    }
+
+    eprintln!(
+        "Reinstalling global packages from Node@v{} into Node@v{}: {}",
+        from,
+        to,
+        pkgs.join(", ")
+    );
+
+    install::install_global(&target, &pkgs)
+}
@@ -1,197 +1,111 @@
+use crate::module::Context;
 use anyhow::Result;
-use once_cell::sync::Lazy;
-use std::{ffi::OsStr, sync::Mutex};
+use args::NpmArgs;
+use std::ffi::OsStr;
 
+mod args;
 mod common;
+mod default_packages;
 mod install;
-mod link;
-mod uninstall;
-mod unlink;
-
-static NEED_REMOVE_PACKAGES: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
-static SKIP_NEXT: Lazy<Mutex<bool>> = Lazy::new(|| Mutex::new(false));
-
-/// Aliases that npm supports for the 'install' command
-const NPM_INSTALL_ALIASES: [&str; 12] = [
-    "i", "in", "ins", "inst", "insta", "instal", "install", "isnt", "isnta", "isntal", "isntall",
-    "add",
-];
-/// Aliases that npm supports for the 'uninstall' command
-const NPM_UNINSTALL_ALIASES: [&str; 5] = ["un", "uninstall", "remove", "rm", "r"];
-/// Aliases that npm supports for the `update` command
-const NPM_UPDATE_ALIASES: [&str; 4] = ["update", "udpate", "upgrade", "up"];
-/// Aliases that npm supports for the 'link' command
-const NPM_LINK_ALIASES: [&str; 2] = ["link", "ln"];
+mod migrate;
+mod registry;
+mod release;
+mod snapshot;
+mod sync;
+
+pub use default_packages::install_default_packages;
+pub use migrate::reinstall_packages;
+pub use registry::{record_installed, record_removed};
+pub use release::{install_corepack, install_npm, project_npm, release_bin_dir};
+pub use snapshot::Snapshot;
+pub use sync::scan_global_bins;
+
+/// Whether the npm command installs, updates or removes packages, e.g. 'npm ci' or 'npm isntall'
+pub fn changes_packages<S>(args: &[S]) -> bool
+where
+    S: AsRef<OsStr>,
+{
+    NpmArgs::parse(args).changes_packages()
+}
 
 pub enum CommandArg<'a> {
     Global(GlobalCommand<'a>),
-    Intercepted(InterceptedCommand<'a>),
     Standard,
 }
 
 impl<'a> CommandArg<'a> {
-    /// Parse the given set of arguments to see if they correspond to an intercepted npm command
+    /// Parse the given set of arguments to see if they may change the global packages
     pub fn from_npm<S>(args: &'a [S]) -> Self
     where
         S: AsRef<OsStr>,
     {
-        let mut positionals = args.iter().filter(is_positional).map(AsRef::as_ref);
-
-        match positionals.next() {
-            Some(cmd) if NPM_INSTALL_ALIASES.iter().any(|a| a == &cmd) => {
-                if has_global(args) {
-                    let tools: Vec<_> = positionals.collect();
-
-                    if tools.is_empty() {
-                        CommandArg::Standard
-                    } else {
-                        let mut common_args = vec![cmd];
-                        common_args.extend(args.iter().filter(is_flag).map(AsRef::as_ref));
-
-                        CommandArg::Global(GlobalCommand::Install(install::InstallArgs {
-                            common_args,
-                            tools,
-                        }))
-                    }
-                } else {
-                    CommandArg::Standard
-                }
-            }
-            Some(cmd) if NPM_UNINSTALL_ALIASES.iter().any(|a| a == &cmd) => {
-                if has_global(args) {
-                    let tools: Vec<_> = positionals.collect();
-                    if tools.is_empty() {
-                        CommandArg::Standard
-                    } else {
-                        let mut common_args = vec![cmd];
-                        common_args.extend(args.iter().filter(is_flag).map(AsRef::as_ref));
-
-                        CommandArg::Global(GlobalCommand::Uninstall(uninstall::UninstallArgs {
-                            common_args,
-                            tools,
-                        }))
-                    }
-                } else {
-                    CommandArg::Standard
-                }
-            }
-            Some(cmd) if cmd == "unlink" => {
-                if has_global(args) {
-                    let mut common_args = vec![cmd];
-                    common_args.extend(args.iter().filter(is_flag).map(AsRef::as_ref));
-                    let tools: Vec<_> = positionals.collect();
-
-                    CommandArg::Intercepted(InterceptedCommand::Unlink(unlink::UnlinkArgs {
-                        common_args,
-                        tools,
-                    }))
-                } else {
-                    CommandArg::Standard
-                }
-            }
-            Some(cmd) if NPM_LINK_ALIASES.iter().any(|a| a == &cmd) => {
-                // Much like install, the common args for a link are the command combined with any flags
-                let mut common_args = vec![cmd];
-                common_args.extend(args.iter().filter(is_flag).map(AsRef::as_ref));
-                let tools: Vec<_> = positionals.collect();
-
-                CommandArg::Intercepted(InterceptedCommand::Link(link::LinkArgs {
-                    common_args,
-                    tools,
-                }))
-            }
-            Some(cmd) if NPM_UPDATE_ALIASES.iter().any(|a| a == &cmd) => {
-                if has_global(args) {
-                    // Once again, the common args are the command combined with any flags
-                    let mut common_args = vec![cmd];
-                    common_args.extend(args.iter().filter(is_flag).map(AsRef::as_ref));
-                    let tools: Vec<_> = positionals.collect();
-
-                    CommandArg::Global(GlobalCommand::Upgrade(install::InstallArgs {
-                        common_args,
-                        tools,
-                    }))
-                } else {
-                    CommandArg::Standard
-                }
-            }
-            _ => CommandArg::Standard,
+        let args = NpmArgs::parse(args);
+        // 'npm link' in a package directory links it into the global packages without '--global',
+        // the read-only commands (e.g. 'ls', 'root' or 'outdated') are run without snapshots
+        let is_link = args.command() == Some("link");
+        if !(is_link || args.global && args.changes_packages()) {
+            return CommandArg::Standard;
         }
+
+        CommandArg::Global(GlobalCommand { flags: args.flags })
     }
 }
 
-pub enum GlobalCommand<'a> {
-    Install(install::InstallArgs<'a>),
-    Uninstall(uninstall::UninstallArgs<'a>),
-    Upgrade(install::InstallArgs<'a>),
+/// An npm command that may change the global packages,
+/// whatever it changes is found by comparing the global packages before and after it
+pub struct GlobalCommand<'a> {
+    /// The flags with their values, which may point npm to another global prefix
+    flags: Vec<&'a OsStr>,
 }
 
 impl GlobalCommand<'_> {
-    pub fn before_executor(&self) -> Result<()> {
-        match self {
-            GlobalCommand::Install(_cmd) => Ok(()),
-            GlobalCommand::Uninstall(cmd) => cmd.before_executor(),
-            GlobalCommand::Upgrade(_cmd) => Ok(()),
-        }
+    /// Take a snapshot of the global packages before npm changes them
+    pub fn before_executor(&self, context: &Context) -> Snapshot {
+        Snapshot::take(context, &self.flags)
     }
 
-    pub fn after_executor(&self) -> Result<()> {
-        match self {
-            GlobalCommand::Install(cmd) => cmd.after_executor(),
-            GlobalCommand::Uninstall(cmd) => cmd.after_executor(),
-            GlobalCommand::Upgrade(cmd) => cmd.after_executor(),
-        }
+    /// Record the changes made to the global packages and update the shims
+    pub fn after_executor(&self, context: &Context, snapshot: Snapshot) -> Result<()> {
+        snapshot.reconcile(context, &self.flags)
     }
 }
 
-/// An intercepted local command
-pub enum InterceptedCommand<'a> {
-    Link(link::LinkArgs<'a>),
-    Unlink(unlink::UnlinkArgs<'a>),
-}
+#[cfg(test)]
+mod tests {
+    use super::CommandArg;
 
-fn has_global<A>(args: &[A]) -> bool
-where
-    A: AsRef<OsStr>,
-{
-    args.iter()
-        .fold(false, |global, arg| match arg.as_ref().to_str() {
-            Some("-g") | Some("--global") => true,
-            _ => global,
-        })
-}
-
-fn is_flag<A>(arg: &A) -> bool
-where
-    A: AsRef<OsStr>,
-{
-    match arg.as_ref().to_str() {
-        Some(a) => a.starts_with('-'),
-        None => false,
+    fn is_global(args: &str) -> bool {
+        let args = args.split_whitespace().collect::<Vec<_>>();
+        matches!(CommandArg::from_npm(&args), CommandArg::Global(_))
     }
-}
-
-/// https://docs.npmjs.com/cli/v7/commands/npm-install#workspace
-/// https://docs.npmjs.com/cli/v7/commands/npm-link#workspace
-/// We should filter out the arguments passed via '--workspace <name>'
-/// It is a feature introduced in 'npm v7'
-fn is_positional<A>(arg: &A) -> bool
-where
-    A: AsRef<OsStr>,
-{
-    let mut skip_next = SKIP_NEXT.lock().unwrap();
 
-    if *skip_next {
-        *skip_next = false;
-        return false;
-    }
+    #[test]
+    fn only_snapshot_commands_that_change_packages() {
+        for args in [
+            "install -g typescript",
+            "i -g typescript",
+            "uninstall --global typescript",
+            "update -g",
+            "dedupe -g",
+            "isntall -g typescript",
+            "uninst -g typescript",
+            "link",
+            "ln",
+            "unlink -g typescript",
+        ] {
+            assert!(is_global(args), "{args}");
+        }
 
-    if let Some(arg_str) = arg.as_ref().to_str() {
-        if arg_str == "--workspace" {
-            *skip_next = true;
-            return false;
+        for args in [
+            "install typescript",
+            "unlink typescript",
+            "ls -g",
+            "root -g",
+            "outdated -g",
+            "view -g typescript",
+            "exec -g -- tsc",
+        ] {
+            assert!(!is_global(args), "{args}");
         }
     }
-
-    !is_flag(arg)
 }
@@ -0,0 +1,104 @@
+use crate::{
+    module::{PackageJson, Packages},
+    utils::help::{is_core_shim, link_package, unlink_package},
+};
+use anyhow::Result;
+use std::path::Path;
+
+/// Record the global packages installed under the version and link the shims of their bins.
+/// `bin_dir` is the directory the bins were installed into, when it is not the one of the version.
+pub fn record_installed(
+    version: &str,
+    installed: &[(String, PackageJson)],
+    bin_dir: Option<&Path>,
+) -> Result<()> {
+    reconcile(version, installed, &[], bin_dir)
+}
+
+/// Drop the global packages removed from the version
+/// and unlink the shims of the bins no package provides anymore
+pub fn record_removed(version: &str, removed: &[PackageJson]) -> Result<()> {
+    reconcile(version, &[], removed, None)
+}
+
+/// Apply the changes to the global packages of the version to the registry and the shims.
+/// A package that was updated is both removed (as it was) and installed (as it is now).
+pub(super) fn reconcile(
+    version: &str,
+    installed: &[(String, PackageJson)],
+    removed: &[PackageJson],
+    bin_dir: Option<&Path>,
+) -> Result<()> {
+    apply(Scope::Version(version), installed, removed, bin_dir)
+}
+
+/// Apply the changes to the global packages in a global directory shared by the Node versions
+/// (e.g. by pnpm) to the registry and the shims, their bins are in the bin directory
+pub(super) fn reconcile_shared(
+    global_dir: &Path,
+    installed: &[(String, PackageJson)],
+    removed: &[PackageJson],
+    bin_dir: Option<&Path>,
+) -> Result<()> {
+    apply(Scope::Shared(global_dir), installed, removed, bin_dir)
+}
+
+/// Where the global packages were installed
+enum Scope<'a> {
+    /// Under the Node version
+    Version(&'a str),
+    /// Into the global directory shared by the Node versions
+    Shared(&'a Path),
+}
+
+fn apply(
+    scope: Scope,
+    installed: &[(String, PackageJson)],
+    removed: &[PackageJson],
+    bin_dir: Option<&Path>,
+) -> Result<()> {
+    let installed = installed
+        .iter()
+        .filter(|(_, package)| package.bin.is_some())
+        .collect::<Vec<_>>();
+    if installed.is_empty() && removed.is_empty() {
+        return Ok(());
+    }
+
+    let mut packages = Packages::new()?;
+    let orphaned = match scope {
+        Scope::Version(version) => {
+            let orphaned = packages.record_uninstalled(version, removed);
+            for (name, package) in &installed {
+                packages.record_installed(version, name, package).bin_dir =
+                    bin_dir.map(Path::to_path_buf);
+            }
+            orphaned
+        }
+        Scope::Shared(global_dir) => {
+            let orphaned = packages.record_shared_uninstalled(global_dir, removed);
+            for (name, package) in &installed {
+                packages
+                    .record_shared_installed(global_dir, name, package)
+                    .bin_dir = bin_dir.map(Path::to_path_buf);
+            }
+            orphaned
+        }
+    };
+    packages.save()?;
+
+    // the bins an updated package still provides keep their shims
+    for bin in orphaned
+        .iter()
+        .filter(|bin| !is_core_shim(bin) && packages.can_be_removed(bin))
+    {
+        unlink_package(bin)?;
+    }
+    for bin in installed
+        .iter()
+        .flat_map(|(_, package)| package.bin_names())
+    {
+        link_package(&bin)?;
+    }
+    Ok(())
+}
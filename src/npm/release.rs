@@ -0,0 +1,137 @@
+use super::install;
+use crate::{
+    module::{
+        Context, NodeVersionResolver, PackageJson, PackageManager, Setting, VersionFile,
+        find_nvmdrc, nvmd_home,
+    },
+    utils::command,
+};
+use anyhow::{Result, anyhow, bail};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Install the npm release into the global prefix of the version of the context,
+/// returns the version of npm now installed
+pub fn install_npm(context: &Context, version: &str) -> Result<String> {
+    install::install_global(context, &[format!("npm@{version}")])?;
+    Ok(bundled_npm(context).unwrap_or_else(|| version.to_string()))
+}
+
+/// The version of npm the project of the directory uses: the 'npm' field of its version file,
+/// or the 'packageManager' field of its 'package.json'
+pub fn project_npm(dir: &Path) -> Result<Option<String>> {
+    if let Some(path) = find_nvmdrc(dir)?
+        && let Some(version) = VersionFile::read(&path)?.npm()
+    {
+        return Ok(Some(version.to_string()));
+    }
+
+    Ok(PackageManager::find(dir)
+        .filter(|package_manager| package_manager.name == "npm")
+        .map(|package_manager| package_manager.version))
+}
+
+/// The bin directory of the npm release to run instead of the npm of the version of the context,
+/// none if the npm of the version matches the release already (e.g. '10' or '10.8.2').
+/// The releases are installed into '$NVMD_HOME/npm/<version>' the first time they are used,
+/// a partial version then runs the latest matching release installed.
+pub fn release_bin_dir(context: &Context, version: &str) -> Result<Option<PathBuf>> {
+    let npm_dir = nvmd_home()?.npm_dir();
+    let installed = NodeVersionResolver::latest_in(&npm_dir, Some(version)).map_err(|_| {
+        anyhow!(
+            "Invalid version of npm {:?}, it must be exact or partial, e.g. '10.8.2' or '10'",
+            version
+        )
+    })?;
+
+    if bundled_npm(context)
+        .and_then(|bundled| NodeVersionResolver::parse(&bundled).ok())
+        .is_some_and(|bundled| NodeVersionResolver::matches(version, &bundled).unwrap_or(false))
+    {
+        return Ok(None);
+    }
+
+    let prefix = match installed {
+        Some(installed) => npm_dir.join(installed.to_string()),
+        None => install_release(context, "npm", Some(version), &npm_dir)?,
+    };
+    Ok(Some(bin_dir(prefix)))
+}
+
+/// Install the corepack release of the 'corepack_version' setting (the latest by default)
+/// into '$NVMD_HOME/corepack/<version>', for the versions of Node.js that no longer bundle it.
+/// Nothing is installed when a matching release (any release without the setting) is installed.
+pub fn install_corepack(context: &Context) -> Result<()> {
+    let version = Setting::global()?.get_corepack_version();
+    let dir = nvmd_home()?.corepack_dir();
+    let installed = NodeVersionResolver::latest_in(&dir, version.as_deref()).map_err(|_| {
+        anyhow!(
+            "Invalid 'corepack_version' setting {:?}, it must be exact or partial, e.g. '0.29.4' or '0.29'",
+            version.as_deref().unwrap_or_default()
+        )
+    })?;
+    if installed.is_none() {
+        install_release(context, "corepack", version.as_deref(), &dir)?;
+    }
+    Ok(())
+}
+
+/// The version of npm installed in the global prefix of the version of the context
+fn bundled_npm(context: &Context) -> Option<String> {
+    let install_dir = context.install_dir().ok()?;
+    PackageJson::new(&node_modules(&install_dir), "npm").version
+}
+
+/// Install a release of the package into a prefix of its own under the directory, named after
+/// the version installed. It goes into a temporary directory first, so an interrupted install is never used.
+fn install_release(
+    context: &Context,
+    name: &str,
+    version: Option<&str>,
+    dir: &Path,
+) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let temp = tempfile::Builder::new()
+        .prefix(".install-")
+        .tempdir_in(dir)?;
+
+    let spec = format!("{}@{}", name, version.unwrap_or("latest"));
+    eprintln!("Installing {}...", spec);
+    let mut command = command::create_command("npm");
+    command
+        .args(["install", "--global", "--no-audit", "--no-fund", "--prefix"])
+        .arg(temp.path())
+        .arg(&spec)
+        // the output of the command run afterwards may be piped
+        .stdout(io::stderr());
+    context.apply_env(&mut command)?;
+
+    if !command.status()?.success() {
+        bail!("Failed to install {}", spec);
+    }
+    let Some(installed) = PackageJson::new(&node_modules(temp.path()), name).version else {
+        bail!("Failed to install {}", spec);
+    };
+
+    let prefix = dir.join(installed);
+    if !prefix.is_dir() {
+        fs::rename(temp.path(), &prefix)?;
+    }
+    Ok(prefix)
+}
+
+fn node_modules(prefix: &Path) -> PathBuf {
+    match cfg!(windows) {
+        true => prefix.join("node_modules"),
+        false => prefix.join("lib").join("node_modules"),
+    }
+}
+
+fn bin_dir(prefix: PathBuf) -> PathBuf {
+    match cfg!(windows) {
+        true => prefix,
+        false => prefix.join("bin"),
+    }
+}
@@ -0,0 +1,168 @@
+use super::{common, registry};
+use crate::module::{Context, PackageJson};
+use anyhow::Result;
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+/// The top-level global packages and their 'package.json' at some point,
+/// so the changes a command makes to them can be found afterwards
+pub struct Snapshot {
+    /// The global 'node_modules', none if it could not be found
+    root: Option<PathBuf>,
+    packages: BTreeMap<String, PackageJson>,
+}
+
+impl Snapshot {
+    /// Read the global packages of the version of the context.
+    /// The global 'node_modules' may not exist yet, it is then taken as empty.
+    pub fn take(context: &Context, flags: &[&OsStr]) -> Self {
+        Self::read(common::get_npm_prefix(context, flags).ok())
+    }
+
+    /// Read the global packages in the global 'node_modules',
+    /// which may also be one shared by the Node versions (e.g. the one of pnpm)
+    pub fn read(root: Option<PathBuf>) -> Self {
+        let packages = match &root {
+            Some(root) => common::global_packages(root, true)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|name| {
+                    let package = PackageJson::new(root, &name);
+                    (name, package)
+                })
+                .collect(),
+            None => BTreeMap::new(),
+        };
+
+        Self { root, packages }
+    }
+
+    /// Take the snapshot again and apply the changes since this one to the registry and the shims.
+    /// The bins are recorded in the bin directory of the global prefix, when the prefix was moved
+    /// (e.g. with '--prefix' or an '.npmrc') away from the version.
+    pub fn reconcile(&self, context: &Context, flags: &[&OsStr]) -> Result<()> {
+        let after = Self::take(context, flags);
+        let (installed, removed) = self.diff(&after);
+
+        let version = context.get_version().unwrap_or_default();
+        let bin_dir = after
+            .root
+            .as_deref()
+            .and_then(common::global_bin_dir)
+            .filter(|bin_dir| context.bin_path().ok().as_ref() != Some(bin_dir));
+        registry::reconcile(&version, &installed, &removed, bin_dir.as_deref())
+    }
+
+    /// Apply the changes between this snapshot and the one after to the registry and the shims,
+    /// for a global 'node_modules' shared by the Node versions. The packages are recorded under
+    /// the global directory when their bins are linked into `bin_dir`, and under the version
+    /// of the context when it is none (their bins are then in the bin directory of the version).
+    pub fn reconcile_shared(
+        &self,
+        context: &Context,
+        after: &Self,
+        bin_dir: Option<&Path>,
+    ) -> Result<()> {
+        let Some(root) = &after.root else {
+            return Ok(());
+        };
+        let (installed, removed) = self.diff(after);
+
+        // the removed packages may have been recorded under the other scope before
+        let version = context.get_version().unwrap_or_default();
+        match bin_dir {
+            Some(bin_dir) => {
+                registry::record_removed(&version, &removed)?;
+                registry::reconcile_shared(root, &installed, &removed, Some(bin_dir))
+            }
+            None => {
+                registry::reconcile_shared(root, &[], &removed, None)?;
+                registry::reconcile(&version, &installed, &removed, None)
+            }
+        }
+    }
+
+    /// The packages added or changed, and the packages removed or changed (as they were)
+    fn diff(&self, after: &Self) -> (Vec<(String, PackageJson)>, Vec<PackageJson>) {
+        let mut installed = vec![];
+        let mut removed = vec![];
+        for (name, package) in &after.packages {
+            match self.packages.get(name) {
+                Some(before) if before == package => {}
+                Some(before) => {
+                    removed.push(named(name, before));
+                    installed.push((name.clone(), package.clone()));
+                }
+                None => installed.push((name.clone(), package.clone())),
+            }
+        }
+        for (name, package) in &self.packages {
+            if !after.packages.contains_key(name) {
+                removed.push(named(name, package));
+            }
+        }
+        (installed, removed)
+    }
+}
+
+/// The package as it is installed, an alias is installed under another name than its own
+fn named(name: &str, package: &PackageJson) -> PackageJson {
+    PackageJson {
+        name: Some(name.to_string()),
+        ..package.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Snapshot;
+    use crate::module::PackageJson;
+    use std::collections::BTreeMap;
+
+    fn package(version: &str, bin: &str) -> PackageJson {
+        serde_json::from_str(&format!(
+            r#"{{"version":"{version}","bin":{{"{bin}":"cli.js"}}}}"#
+        ))
+        .unwrap()
+    }
+
+    fn snapshot(packages: &[(&str, PackageJson)]) -> Snapshot {
+        Snapshot {
+            root: None,
+            packages: packages
+                .iter()
+                .map(|(name, package)| (name.to_string(), package.clone()))
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn diff_added_changed_and_removed_packages() {
+        let before = snapshot(&[
+            ("typescript", package("5.3.0", "tsc")),
+            ("pnpm", package("8.0.0", "pnpm")),
+            ("yarn", package("1.22.0", "yarn")),
+        ]);
+        let after = snapshot(&[
+            ("typescript", package("5.4.2", "tsc")),
+            ("yarn", package("1.22.0", "yarn")),
+            ("eslint", package("9.0.0", "eslint")),
+        ]);
+
+        let (installed, removed) = before.diff(&after);
+        let installed = installed
+            .iter()
+            .map(|(n, _)| n.as_str())
+            .collect::<Vec<_>>();
+        let removed = removed
+            .iter()
+            .map(|p| (p.name.as_deref().unwrap(), p.version.as_deref().unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(installed, ["eslint", "typescript"]);
+        assert_eq!(removed, [("typescript", "5.3.0"), ("pnpm", "8.0.0")]);
+    }
+}
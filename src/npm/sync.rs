@@ -0,0 +1,76 @@
+use super::common;
+use crate::{
+    module::{
+        COREPACK_MANAGERS, Context, NodeVersionResolver, PackageJson, PackageRecord, PackagesData,
+    },
+    utils::help::is_core_shim,
+};
+use anyhow::Result;
+use std::fs;
+
+/// Scan the global packages of every installed version and record the bins they provide,
+/// the way the shims registry records them.
+/// Only the bins that are actually present in the bin directory of the version are counted.
+pub fn scan_global_bins() -> Result<PackagesData> {
+    let mut data = PackagesData::new();
+    for version in NodeVersionResolver::installed()? {
+        let version = version.to_string();
+        let context = Context::from_version(version.clone());
+
+        // the package managers enabled by corepack
+        for manager in COREPACK_MANAGERS {
+            let package = PackageJson::corepack(manager);
+            let bins = package.bin_names();
+            if bins.is_empty() || !bins.iter().all(|bin| is_corepack_shim(&context, bin)) {
+                continue;
+            }
+            data.entry(version.clone()).or_default().insert(
+                package.name.unwrap_or_default(),
+                PackageRecord {
+                    bins,
+                    ..Default::default()
+                },
+            );
+        }
+
+        // a version without npm has no global packages
+        let Ok(root) = common::get_npm_prefix(&context, &[]) else {
+            continue;
+        };
+
+        for pkg in common::global_packages(&root, true)? {
+            let package = PackageJson::new(&root, &pkg);
+            let bins = package
+                .bin_names()
+                .into_iter()
+                .filter(|bin| !is_core_shim(bin) && context.check_lib_path(bin.as_ref()).is_ok())
+                .collect::<Vec<_>>();
+            if bins.is_empty() {
+                continue;
+            }
+
+            data.entry(version.clone()).or_default().insert(
+                pkg,
+                PackageRecord {
+                    version: package.version,
+                    bins,
+                    bin_dir: None,
+                },
+            );
+        }
+    }
+    Ok(data)
+}
+
+/// Whether the bin of the version is one of the shims corepack creates
+fn is_corepack_shim(context: &Context, bin: &str) -> bool {
+    let Ok(bin_dir) = context.bin_path() else {
+        return false;
+    };
+    match cfg!(windows) {
+        true => fs::read_to_string(bin_dir.join(format!("{bin}.cmd")))
+            .is_ok_and(|content| content.contains("corepack")),
+        false => fs::read_link(bin_dir.join(bin))
+            .is_ok_and(|target| target.to_string_lossy().contains("corepack")),
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{
+    module::Context,
+    utils::{args::flag_value, command},
+};
+use anyhow::{Context as AnyhowContext, Result, anyhow, bail};
+use std::{
+    env,
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+/// The environment variable pnpm installs its global bins into
+const PNPM_HOME_ENV: &str = "PNPM_HOME";
+
+/// Find the global 'node_modules' of pnpm, the same as `pnpm root -g`.
+///
+/// pnpm keeps its global packages in '{global-dir}/{layout version}/node_modules',
+/// where the global dir is the '--global-dir' flag or '$PNPM_HOME/global'.
+/// `pnpm root -g` is only spawned when that fails.
+pub(super) fn global_root(context: &Context, pnpm: &Path, flags: &[&OsStr]) -> Result<PathBuf> {
+    let global_dir = flag_value(flags, "--global-dir")
+        .map(PathBuf::from)
+        .or_else(|| pnpm_home().map(|home| home.join("global")));
+    if let Some(root) = global_dir.and_then(|dir| latest_layout(&dir)) {
+        return Ok(root);
+    }
+
+    pnpm_global(context, pnpm, "root")
+}
+
+/// Find the directory pnpm installs the global bins into, the same as `pnpm bin -g`
+pub(super) fn global_bin_dir(context: &Context, pnpm: &Path, flags: &[&OsStr]) -> Result<PathBuf> {
+    if let Some(dir) = flag_value(flags, "--global-bin-dir")
+        .map(PathBuf::from)
+        .or_else(pnpm_home)
+    {
+        return Ok(dir);
+    }
+
+    pnpm_global(context, pnpm, "bin")
+}
+
+fn pnpm_home() -> Option<PathBuf> {
+    env::var_os(PNPM_HOME_ENV)
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The 'node_modules' of the newest layout version in the global dir, e.g. 'global/5/node_modules'
+fn latest_layout(global_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(global_dir)
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let layout = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            let root = entry.path().join("node_modules");
+            root.is_dir().then_some((layout, root))
+        })
+        .max_by_key(|(layout, _)| *layout)
+        .map(|(_, root)| root)
+}
+
+fn pnpm_global(context: &Context, pnpm: &Path, cmd: &str) -> Result<PathBuf> {
+    let mut command = command::create_command(pnpm);
+    command.args([cmd, "--global"]).stdout(Stdio::piped());
+    context.apply_env(&mut command)?;
+
+    let output = command
+        .output()
+        .with_context(|| anyhow!("No valid pnpm global directory found"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    for line in stdout.lines() {
+        let pb = PathBuf::from(line.trim());
+        if pb.is_dir() {
+            return Ok(pb);
+        }
+    }
+
+    bail!("No valid pnpm global directory found");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::latest_layout;
+    use std::fs;
+
+    #[test]
+    fn choose_the_newest_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        for layout in ["4", "5", "10", "tmp"] {
+            fs::create_dir_all(dir.path().join(layout).join("node_modules")).unwrap();
+        }
+        fs::create_dir_all(dir.path().join("11")).unwrap();
+
+        assert_eq!(
+            latest_layout(dir.path()),
+            Some(dir.path().join("10").join("node_modules"))
+        );
+    }
+}
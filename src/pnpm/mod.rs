@@ -0,0 +1,136 @@
+use crate::{
+    core::SharedGlobalCommand,
+    module::Context,
+    utils::args::{has_global, split_args},
+};
+use anyhow::Result;
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+};
+
+mod common;
+
+/// Aliases that pnpm supports for the 'add' command, 'install' adds the packages when some are given
+const PNPM_ADD_ALIASES: [&str; 3] = ["add", "install", "i"];
+/// Aliases that pnpm supports for the 'remove' command
+const PNPM_REMOVE_ALIASES: [&str; 5] = ["remove", "rm", "uninstall", "un", "uni"];
+/// Aliases that pnpm supports for the 'update' command
+const PNPM_UPDATE_ALIASES: [&str; 3] = ["update", "up", "upgrade"];
+/// Aliases that pnpm supports for the 'link' command
+const PNPM_LINK_ALIASES: [&str; 2] = ["link", "ln"];
+/// The flags that take the next argument as their value
+const PNPM_VALUE_FLAGS: [&str; 9] = [
+    "--filter",
+    "-F",
+    "--dir",
+    "-C",
+    "--global-dir",
+    "--global-bin-dir",
+    "--store-dir",
+    "--reporter",
+    "--loglevel",
+];
+
+pub enum CommandArg<'a> {
+    Global(GlobalCommand<'a>),
+    Standard,
+}
+
+impl<'a> CommandArg<'a> {
+    /// Parse the given set of arguments to see if they correspond to a global pnpm command
+    pub fn from_pnpm<S>(args: &'a [S]) -> Self
+    where
+        S: AsRef<OsStr>,
+    {
+        let (positionals, flags) = split_args(args, &PNPM_VALUE_FLAGS);
+        if !has_global(&flags) {
+            return CommandArg::Standard;
+        }
+
+        let Some((cmd, tools)) = positionals.split_first() else {
+            return CommandArg::Standard;
+        };
+        let is_changing = match cmd.to_str() {
+            Some(cmd) if PNPM_ADD_ALIASES.contains(&cmd) || PNPM_REMOVE_ALIASES.contains(&cmd) => {
+                !tools.is_empty()
+            }
+            Some(cmd) if PNPM_UPDATE_ALIASES.contains(&cmd) => true,
+            // 'pnpm link --global <pkg>' links a global package into the current project instead
+            Some(cmd) if PNPM_LINK_ALIASES.contains(&cmd) => tools.is_empty(),
+            _ => false,
+        };
+        if !is_changing {
+            return CommandArg::Standard;
+        }
+
+        CommandArg::Global(GlobalCommand { flags })
+    }
+}
+
+/// A pnpm command that changes the global packages,
+/// whatever it changes is found by comparing the global packages before and after it
+pub struct GlobalCommand<'a> {
+    /// The flags, with the values of the flags that take one
+    flags: Vec<&'a OsStr>,
+}
+
+impl<'a> SharedGlobalCommand<'a> for GlobalCommand<'a> {
+    fn parse(args: &'a [OsString]) -> Option<Self> {
+        match CommandArg::from_pnpm(args) {
+            CommandArg::Global(global) => Some(global),
+            CommandArg::Standard => None,
+        }
+    }
+
+    fn global_root(&self, context: &Context, pnpm: &Path) -> Result<PathBuf> {
+        common::global_root(context, pnpm, &self.flags)
+    }
+
+    /// pnpm links the global bins into its own directory, shared by the Node versions
+    fn global_bin_dir(&self, context: &Context, pnpm: &Path) -> Result<Option<PathBuf>> {
+        common::global_bin_dir(context, pnpm, &self.flags).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommandArg;
+
+    fn is_global(args: &str) -> bool {
+        let args = args.split_whitespace().collect::<Vec<_>>();
+        matches!(CommandArg::from_pnpm(&args), CommandArg::Global(_))
+    }
+
+    #[test]
+    fn parse_global_commands() {
+        for args in [
+            "add -g typescript",
+            "i --global typescript",
+            "rm -g typescript",
+            "up -g",
+            "link --global",
+        ] {
+            assert!(is_global(args), "{args}");
+        }
+
+        for args in [
+            "add typescript",
+            "install -g",
+            "link --global typescript",
+            "-g --filter add list",
+        ] {
+            assert!(!is_global(args), "{args}");
+        }
+    }
+
+    #[test]
+    fn skip_the_values_of_flags() {
+        let CommandArg::Global(cmd) =
+            CommandArg::from_pnpm(&["add", "--global-dir", "/tmp/pnpm", "-g", "typescript"])
+        else {
+            panic!("expected a global command");
+        };
+        assert_eq!(cmd.flags, ["--global-dir", "/tmp/pnpm", "-g"]);
+    }
+}
@@ -1,10 +1,35 @@
-use std::process::exit;
+use crate::module::Setting;
+use anyhow::Result;
+#[cfg(unix)]
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, ExitStatus, exit};
+#[cfg(unix)]
+use std::sync::atomic::AtomicI32;
 use std::sync::atomic::{AtomicBool, Ordering};
 
 static SHIM_HAS_CONTROL: AtomicBool = AtomicBool::new(false);
 const INTERRUPTED_EXIT_CODE: i32 = 130;
 
-pub fn pass_control_to_shim() {
+/// The pid of the supervised child process, 0 if it has not been spawned yet
+#[cfg(unix)]
+static CHILD_PID: AtomicI32 = AtomicI32::new(0);
+/// A signal received while there was no child process to relay it to
+#[cfg(unix)]
+static PENDING_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+/// Signals relayed to the supervised child process, when they were sent by another process.
+/// The ones generated by the terminal (Ctrl+\\, a hangup) already reach the whole foreground
+/// process group, like SIGINT which is never relayed.
+#[cfg(unix)]
+const FORWARDED_SIGNALS: [libc::c_int; 5] = [
+    libc::SIGTERM,
+    libc::SIGHUP,
+    libc::SIGQUIT,
+    libc::SIGUSR1,
+    libc::SIGUSR2,
+];
+
+fn pass_control_to_shim() {
     SHIM_HAS_CONTROL.store(true, Ordering::SeqCst);
 }
 
@@ -23,3 +48,294 @@ pub fn setup_signal_handler() {
         );
     }
 }
+
+/// Run the command as a child process and wait for it to exit.
+/// On Unix, the signals in `FORWARDED_SIGNALS` received meanwhile are relayed to the child,
+/// so e.g. a SIGTERM from Docker or systemd reaches it instead of being lost.
+pub fn supervise(command: &mut Command) -> Result<ExitStatus> {
+    pass_control_to_shim();
+
+    #[cfg(unix)]
+    install_forwarding_handlers();
+
+    let mut child = command.spawn()?;
+
+    #[cfg(unix)]
+    {
+        CHILD_PID.store(child.id() as i32, Ordering::SeqCst);
+        let pending = PENDING_SIGNAL.swap(0, Ordering::SeqCst);
+        if pending != 0 {
+            unsafe { libc::kill(child.id() as libc::pid_t, pending) };
+        }
+    }
+
+    let status = child.wait();
+
+    #[cfg(unix)]
+    CHILD_PID.store(0, Ordering::SeqCst);
+
+    Ok(status?)
+}
+
+#[cfg(unix)]
+fn install_forwarding_handlers() {
+    for signal in FORWARDED_SIGNALS {
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = forward_signal as *const () as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART | libc::SA_SIGINFO;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(signal, &action, std::ptr::null_mut());
+        }
+    }
+}
+
+/// Only async-signal-safe operations are allowed here
+#[cfg(unix)]
+extern "C" fn forward_signal(
+    signal: libc::c_int,
+    info: *mut libc::siginfo_t,
+    _context: *mut libc::c_void,
+) {
+    if is_from_terminal(info) {
+        return;
+    }
+
+    let pid = CHILD_PID.load(Ordering::SeqCst);
+    if pid > 0 {
+        unsafe { libc::kill(pid, signal) };
+    } else {
+        PENDING_SIGNAL.store(signal, Ordering::SeqCst);
+    }
+}
+
+/// Whether the kernel generated the signal, as it does for the ones of the terminal.
+/// The signals sent with kill(2) are relayed, even when their sender is in another
+/// pid namespace (e.g. 'docker stop') and so has no pid here.
+#[cfg(unix)]
+fn is_from_terminal(info: *const libc::siginfo_t) -> bool {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    return !info.is_null() && unsafe { (*info).si_code } == libc::SI_KERNEL;
+
+    // the other systems do not tell them apart, every signal is relayed
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let _ = info;
+        false
+    }
+}
+
+/// Exit with the same status as the child process.
+///
+/// When the child was terminated by a signal, the signal is raised again on ourselves, so the
+/// parent observes the same termination, unless the 'reraise_signals' setting is off.
+/// Otherwise, or if we survive it, we exit with the shell convention `128 + signal number`.
+pub fn exit_with(status: ExitStatus) -> ! {
+    let reraise = Setting::global()
+        .map(|setting| setting.get_reraise_signals())
+        .unwrap_or(true);
+    terminate(status, reraise)
+}
+
+/// Exit with the code, unless a signal was sent to us after the child process exited
+pub fn exit_with_code(code: i32) -> ! {
+    #[cfg(unix)]
+    raise_pending();
+
+    exit(code)
+}
+
+fn terminate(status: ExitStatus, reraise_signals: bool) -> ! {
+    #[cfg(unix)]
+    raise_pending();
+
+    #[cfg(unix)]
+    if let Some(signal) = status.signal() {
+        if status.core_dumped() {
+            // Raising the signal again would dump our own core over the child's one
+            eprintln!(
+                "{}",
+                console::style(format!("terminated by signal {} (core dumped)", signal)).red()
+            );
+        } else if reraise_signals {
+            reraise(signal);
+        }
+    }
+
+    exit(exit_code(&status))
+}
+
+/// The exit code of the child process, `128 + signal number` if it was terminated by a signal
+pub fn exit_code(status: &ExitStatus) -> i32 {
+    #[cfg(unix)]
+    if let Some(signal) = status.signal() {
+        return 128 + signal;
+    }
+
+    status.code().unwrap_or(1)
+}
+
+/// Raise the signal received after the child process exited, it was meant for us
+#[cfg(unix)]
+fn raise_pending() {
+    let pending = PENDING_SIGNAL.swap(0, Ordering::SeqCst);
+    if pending != 0 {
+        reraise(pending);
+    }
+}
+
+#[cfg(unix)]
+fn reraise(signal: i32) {
+    // Restore the default disposition first, our own handlers (e.g. Ctrl+C) would swallow it
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{PENDING_SIGNAL, exit_code, is_from_terminal, supervise, terminate};
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+    use std::sync::atomic::Ordering;
+    use std::{env, thread, time::Duration};
+
+    const TERMINATE_ENV: &str = "NVMD_TEST_TERMINATE";
+
+    fn run(script: &str) -> std::process::ExitStatus {
+        Command::new("sh").args(["-c", script]).status().unwrap()
+    }
+
+    #[test]
+    fn map_signal_deaths_to_shell_exit_codes() {
+        let status = run("kill -TERM $$");
+        assert_eq!(status.signal(), Some(libc::SIGTERM));
+        assert_eq!(exit_code(&status), 143);
+
+        let status = run("kill -KILL $$");
+        assert_eq!(status.signal(), Some(libc::SIGKILL));
+        assert_eq!(exit_code(&status), 137);
+
+        let status = run("ulimit -c 0; kill -SEGV $$");
+        assert_eq!(status.signal(), Some(libc::SIGSEGV));
+        assert!(!status.core_dumped());
+        assert_eq!(exit_code(&status), 139);
+    }
+
+    #[test]
+    fn keep_exit_codes_of_normal_exits() {
+        assert_eq!(exit_code(&run("exit 0")), 0);
+        assert_eq!(exit_code(&run("exit 3")), 3);
+    }
+
+    /// Run in a child test process by 'terminate_with_the_signal_of_the_child'
+    #[test]
+    fn terminate_like_the_child() {
+        let Ok(reraise) = env::var(TERMINATE_ENV) else {
+            return;
+        };
+        terminate(run("kill -TERM $$"), reraise == "true");
+    }
+
+    /// Run in a child test process by 'raise_the_signals_received_after_the_child_exited'
+    #[test]
+    fn receive_a_signal_after_the_child_exited() {
+        if env::var(TERMINATE_ENV).as_deref() != Ok("pending") {
+            return;
+        }
+        let status = supervise(Command::new("true").arg("")).unwrap();
+        unsafe { libc::kill(libc::getpid(), libc::SIGUSR2) };
+        // another thread of the test harness may handle it
+        while PENDING_SIGNAL.load(Ordering::SeqCst) == 0 {
+            thread::sleep(Duration::from_millis(10));
+        }
+        terminate(status, true);
+    }
+
+    #[test]
+    fn raise_the_signals_received_after_the_child_exited() {
+        let status = Command::new(env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "signal::tests::receive_a_signal_after_the_child_exited",
+            ])
+            .env(TERMINATE_ENV, "pending")
+            .output()
+            .unwrap()
+            .status;
+        assert_eq!(status.signal(), Some(libc::SIGUSR2));
+    }
+
+    #[test]
+    fn terminate_with_the_signal_of_the_child() {
+        for (reraise, signal, code) in [
+            ("true", Some(libc::SIGTERM), None),
+            ("false", None, Some(143)),
+        ] {
+            let status = Command::new(env::current_exe().unwrap())
+                .args(["--exact", "signal::tests::terminate_like_the_child"])
+                .env(TERMINATE_ENV, reraise)
+                .output()
+                .unwrap()
+                .status;
+            assert_eq!((status.signal(), status.code()), (signal, code));
+        }
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn only_ignore_the_signals_of_the_terminal() {
+        // a signal sent from another pid namespace has no sender pid
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        info.si_code = libc::SI_USER;
+        assert!(!is_from_terminal(&info));
+        info.si_code = libc::SI_KERNEL;
+        assert!(is_from_terminal(&info));
+        assert!(!is_from_terminal(std::ptr::null()));
+    }
+
+    #[test]
+    fn forward_signals_to_the_supervised_child() {
+        for (signal, code) in [(libc::SIGTERM, 42), (libc::SIGHUP, 43), (libc::SIGUSR1, 44)] {
+            let name = match signal {
+                libc::SIGTERM => "TERM",
+                libc::SIGHUP => "HUP",
+                _ => "USR1",
+            };
+            let mut command = Command::new("sh");
+            command.args([
+                "-c",
+                &format!(
+                    "trap 'exit {}' {}; while :; do sleep 0.05; done",
+                    code, name
+                ),
+            ]);
+
+            // The signal is sent to the shim (this process), not the child
+            let sender = thread::spawn(move || {
+                thread::sleep(Duration::from_millis(500));
+                unsafe { libc::kill(libc::getpid(), signal) };
+            });
+
+            let status = supervise(&mut command).unwrap();
+            sender.join().unwrap();
+            assert_eq!(status.code(), Some(code));
+        }
+
+        // sent with kill(2) by another process
+        let mut command = Command::new("sh");
+        command.args(["-c", "trap 'exit 45' USR2; while :; do sleep 0.05; done"]);
+        let mut sender = Command::new("sh")
+            .args([
+                "-c",
+                &format!("sleep 0.5; kill -USR2 {}", std::process::id()),
+            ])
+            .spawn()
+            .unwrap();
+
+        let status = supervise(&mut command).unwrap();
+        sender.wait().unwrap();
+        assert_eq!(status.code(), Some(45));
+    }
+}
@@ -0,0 +1,73 @@
+use std::ffi::OsStr;
+
+/// Split the arguments of a package manager into the positional ones and the flags.
+/// The values of the flags in `value_flags`, which take the next argument, are kept with the flags.
+pub fn split_args<'a, S>(args: &'a [S], value_flags: &[&str]) -> (Vec<&'a OsStr>, Vec<&'a OsStr>)
+where
+    S: AsRef<OsStr>,
+{
+    let mut positionals = vec![];
+    let mut flags = vec![];
+    let mut args = args.iter().map(AsRef::as_ref);
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some(flag) if value_flags.contains(&flag) => {
+                flags.push(arg);
+                flags.extend(args.next());
+            }
+            Some(flag) if flag.starts_with('-') => flags.push(arg),
+            _ => positionals.push(arg),
+        }
+    }
+    (positionals, flags)
+}
+
+/// Whether the flags contain '-g' or '--global'
+pub fn has_global(flags: &[&OsStr]) -> bool {
+    flags
+        .iter()
+        .any(|flag| *flag == "-g" || *flag == "--global")
+}
+
+/// The value of a flag passed either as '--flag=value' or '--flag value', the last one wins
+pub fn flag_value<'a>(flags: &[&'a OsStr], name: &str) -> Option<&'a str> {
+    let mut value = None;
+    let mut flags = flags.iter().filter_map(|flag| flag.to_str());
+    while let Some(flag) = flags.next() {
+        if flag == name {
+            value = flags.next();
+        } else if let Some(v) = flag.strip_prefix(name).and_then(|v| v.strip_prefix('=')) {
+            value = Some(v);
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flag_value, split_args};
+    use std::ffi::OsStr;
+
+    #[test]
+    fn keep_flag_values_with_the_flags() {
+        let args = [
+            "add",
+            "--dir",
+            "/tmp",
+            "-g",
+            "typescript",
+            "--reporter=silent",
+        ];
+        let (positionals, flags) = split_args(&args, &["--dir"]);
+        assert_eq!(positionals, ["add", "typescript"]);
+        assert_eq!(flags, ["--dir", "/tmp", "-g", "--reporter=silent"]);
+    }
+
+    #[test]
+    fn read_flag_values() {
+        let flags = ["--global", "--global-dir", "/a", "--global-dir=/b"].map(OsStr::new);
+        assert_eq!(flag_value(&flags, "--global-dir"), Some("/b"));
+        assert_eq!(flag_value(&flags[..3], "--global-dir"), Some("/a"));
+        assert_eq!(flag_value(&flags, "--global-bin-dir"), None);
+    }
+}
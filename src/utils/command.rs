@@ -1,8 +1,82 @@
-use std::ffi::OsStr;
+use crate::module::nvmd_home;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use cfg_if::cfg_if;
 
+/// How many shims resolved to another shim above the current process
+pub const SHIM_DEPTH_ENV: &str = "NVMD_SHIM_DEPTH";
+/// The paths those shims resolved to, separated by ' -> '
+pub const SHIM_CHAIN_ENV: &str = "NVMD_SHIM_CHAIN";
+
+pub fn shim_depth() -> usize {
+    env::var(SHIM_DEPTH_ENV)
+        .ok()
+        .and_then(|depth| depth.parse().ok())
+        .unwrap_or(0)
+}
+
+pub fn shim_chain() -> Vec<String> {
+    env::var(SHIM_CHAIN_ENV)
+        .map(|chain| chain.split(" -> ").map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Carry the shim depth and chain to the command of a shim when it resolves to another shim,
+/// so a shim resolving back to itself can be detected. The tools that are not shims start
+/// over, the processes they spawn in turn are not a recursion.
+pub fn track_shim_depth(command: &mut Command) {
+    let path = command
+        .get_envs()
+        .find(|(key, _)| key.eq_ignore_ascii_case("PATH"))
+        .map(|(_, value)| value.map(OsStr::to_os_string))
+        .unwrap_or_else(|| env::var_os("PATH"))
+        .unwrap_or_default();
+
+    match program(command).and_then(|program| resolve_program(program, &path)) {
+        Some(resolved) if is_shim(&resolved) => {
+            let mut chain = shim_chain();
+            chain.push(resolved.display().to_string());
+            command.env(SHIM_DEPTH_ENV, (shim_depth() + 1).to_string());
+            command.env(SHIM_CHAIN_ENV, chain.join(" -> "));
+        }
+        _ => {
+            command.env_remove(SHIM_DEPTH_ENV);
+            command.env_remove(SHIM_CHAIN_ENV);
+        }
+    }
+}
+
+/// The executable the program runs, searched in PATH when it is a bare name
+fn resolve_program(program: &OsStr, path: &OsStr) -> Option<PathBuf> {
+    let program = Path::new(program);
+    if program.components().count() > 1 {
+        return Some(program.to_path_buf());
+    }
+
+    let names = match cfg!(windows) {
+        true => ["exe", "cmd", "bat"]
+            .iter()
+            .map(|ext| program.with_extension(ext).into_os_string())
+            .collect::<Vec<OsString>>(),
+        false => vec![program.as_os_str().to_os_string()],
+    };
+    env::split_paths(path)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Whether the executable is one of the shims in '$NVMD_HOME/bin'
+fn is_shim(executable: &Path) -> bool {
+    let Ok(bin_dir) = nvmd_home().map(|home| home.bin_dir()) else {
+        return false;
+    };
+    let parent = executable.parent().and_then(|dir| dir.canonicalize().ok());
+    parent.is_some() && parent == bin_dir.canonicalize().ok()
+}
+
 cfg_if! {
     if #[cfg(windows)] {
         pub fn create_command<E>(exe: E) -> Command
@@ -16,15 +90,50 @@ cfg_if! {
             // See: https://github.com/rust-lang/rust/issues/42791 For a longer discussion
             let mut command = Command::new("cmd.exe");
             command.arg("/C");
-            command.arg(exe);
+            command.arg(&exe);
             command
         }
+
+        fn program(command: &Command) -> Option<&OsStr> {
+            command.get_args().nth(1)
+        }
     } else {
         pub fn create_command<E>(exe: E) -> Command
         where
             E: AsRef<OsStr>
         {
-            Command::new(exe)
+            Command::new(&exe)
+        }
+
+        fn program(command: &Command) -> Option<&OsStr> {
+            Some(command.get_program())
         }
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::resolve_program;
+    use std::{env, ffi::OsStr, fs};
+
+    #[test]
+    fn resolve_bare_names_from_path() {
+        let root = tempfile::tempdir().unwrap();
+        let dirs = ["a", "b"].map(|name| root.path().join(name));
+        for dir in &dirs {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(dirs[1].join("node"), "").unwrap();
+        let path = env::join_paths(&dirs).unwrap();
+
+        assert_eq!(
+            resolve_program(OsStr::new("node"), &path),
+            Some(dirs[1].join("node"))
+        );
+        assert_eq!(resolve_program(OsStr::new("npm"), &path), None);
+        assert_eq!(
+            resolve_program(OsStr::new("/usr/bin/env"), &path),
+            Some("/usr/bin/env".into())
+        );
+    }
+}
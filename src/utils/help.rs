@@ -1,9 +1,20 @@
 use crate::module::Setting;
 use crate::module::nvmd_home;
-use anyhow::{Context, Result, anyhow, bail};
+use anyhow::{Context, Result, bail};
 use fs_extra::file::{remove, write_all};
 use serde::de::DeserializeOwned;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+/// Shims that are managed by nvmd itself and never belong to a global package
+pub const CORE_SHIMS: [&str; 5] = ["nvmd", "node", "npm", "npx", "corepack"];
+
+pub fn is_core_shim(name: &str) -> bool {
+    CORE_SHIMS.contains(&name)
+}
 
 pub fn read_json<T: DeserializeOwned>(path: &PathBuf) -> Result<T> {
     if !path.exists() {
@@ -27,6 +38,16 @@ pub fn write_json<T: serde::Serialize>(path: &PathBuf, data: &T) -> Result<()> {
     Ok(())
 }
 
+/// Ask the user a yes/no question on the terminal, defaults to no
+pub fn confirm(prompt: &str) -> Result<bool> {
+    eprint!("{} [y/N] ", prompt);
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
 pub fn node_strict_available(version: &str) -> Result<bool> {
     let mut path = Setting::global()?.get_directory()?.join(version);
 
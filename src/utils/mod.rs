@@ -1,3 +1,4 @@
+pub mod args;
 pub mod command;
 pub mod help;
 pub mod notice;
@@ -5,7 +5,7 @@
 use archive::Origin;
 use console::style;
 use indicatif::{ProgressBar, ProgressStyle};
-use terminal_size::{terminal_size, Width};
+use terminal_size::{Width, terminal_size};
 
 pub const MAX_WIDTH: usize = 100;
 const MAX_PROGRESS_WIDTH: usize = 40;
@@ -0,0 +1,78 @@
+use crate::{
+    module::Context,
+    utils::{args::flag_value, command},
+};
+use anyhow::{Context as AnyhowContext, Result, anyhow, bail};
+use std::{
+    env,
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+/// Find the global 'node_modules' of yarn, the same as `yarn global dir` followed by 'node_modules'.
+///
+/// The global folder is the '--global-folder' flag or the 'YARN_GLOBAL_FOLDER' environment variable,
+/// `yarn global dir` is only spawned when neither is set.
+pub(super) fn global_root(context: &Context, yarn: &Path, flags: &[&OsStr]) -> Result<PathBuf> {
+    let global_dir = match flag_value(flags, "--global-folder")
+        .map(PathBuf::from)
+        .or_else(|| env_path("YARN_GLOBAL_FOLDER"))
+    {
+        Some(dir) => dir,
+        None => yarn_global(context, yarn, "dir")?,
+    };
+
+    Ok(global_dir.join("node_modules"))
+}
+
+/// Find the directory yarn installs the global bins into, the same as `yarn global bin`.
+/// Returns none when it is the bin directory of the version, which is the default.
+pub(super) fn global_bin_dir(
+    context: &Context,
+    yarn: &Path,
+    flags: &[&OsStr],
+) -> Result<Option<PathBuf>> {
+    let bin_dir = match flag_value(flags, "--prefix")
+        .map(PathBuf::from)
+        .or_else(|| env_path("YARN_PREFIX"))
+    {
+        Some(prefix) if cfg!(windows) => prefix,
+        Some(prefix) => prefix.join("bin"),
+        None => yarn_global(context, yarn, "bin")?,
+    };
+
+    let version_bin_dir = context.bin_path()?;
+    match fs::canonicalize(&bin_dir).ok() == fs::canonicalize(&version_bin_dir).ok() {
+        true => Ok(None),
+        false => Ok(Some(bin_dir)),
+    }
+}
+
+fn env_path(key: &str) -> Option<PathBuf> {
+    env::var_os(key)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+fn yarn_global(context: &Context, yarn: &Path, cmd: &str) -> Result<PathBuf> {
+    let mut command = command::create_command(yarn);
+    command.args(["global", cmd]).stdout(Stdio::piped());
+    context.apply_env(&mut command)?;
+
+    let output = command
+        .output()
+        .with_context(|| anyhow!("No valid yarn global directory found"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // the bin directory may not exist before the first global install
+    for line in stdout.lines().rev() {
+        let pb = PathBuf::from(line.trim());
+        if pb.is_absolute() {
+            return Ok(pb);
+        }
+    }
+
+    bail!("No valid yarn global directory found");
+}
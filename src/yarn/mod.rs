@@ -0,0 +1,108 @@
+use crate::{core::SharedGlobalCommand, module::Context, utils::args::split_args};
+use anyhow::Result;
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+};
+
+mod common;
+
+/// The flags that take the next argument as their value
+const YARN_VALUE_FLAGS: [&str; 8] = [
+    "--prefix",
+    "--global-folder",
+    "--cwd",
+    "--modules-folder",
+    "--cache-folder",
+    "--link-folder",
+    "--registry",
+    "--network-timeout",
+];
+
+pub enum CommandArg<'a> {
+    Global(GlobalCommand<'a>),
+    Standard,
+}
+
+impl<'a> CommandArg<'a> {
+    /// Parse the given set of arguments to see if they correspond to a `yarn global` command
+    /// that changes the global packages, only yarn classic has it
+    pub fn from_yarn<S>(args: &'a [S]) -> Self
+    where
+        S: AsRef<OsStr>,
+    {
+        let (positionals, flags) = split_args(args, &YARN_VALUE_FLAGS);
+        let [global, cmd, tools @ ..] = positionals.as_slice() else {
+            return CommandArg::Standard;
+        };
+        if *global != "global" {
+            return CommandArg::Standard;
+        }
+
+        let is_changing = match cmd.to_str() {
+            Some("add" | "remove") => !tools.is_empty(),
+            Some("upgrade") => true,
+            _ => false,
+        };
+        if !is_changing {
+            return CommandArg::Standard;
+        }
+
+        CommandArg::Global(GlobalCommand { flags })
+    }
+}
+
+/// A `yarn global` command that changes the global packages,
+/// whatever it changes is found by comparing the global packages before and after it
+pub struct GlobalCommand<'a> {
+    /// The flags, with the values of the flags that take one
+    flags: Vec<&'a OsStr>,
+}
+
+impl<'a> SharedGlobalCommand<'a> for GlobalCommand<'a> {
+    fn parse(args: &'a [OsString]) -> Option<Self> {
+        match CommandArg::from_yarn(args) {
+            CommandArg::Global(global) => Some(global),
+            CommandArg::Standard => None,
+        }
+    }
+
+    fn global_root(&self, context: &Context, yarn: &Path) -> Result<PathBuf> {
+        common::global_root(context, yarn, &self.flags)
+    }
+
+    /// yarn links the global bins into the bin directory of the version by default
+    fn global_bin_dir(&self, context: &Context, yarn: &Path) -> Result<Option<PathBuf>> {
+        common::global_bin_dir(context, yarn, &self.flags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommandArg;
+
+    fn is_global(args: &str) -> bool {
+        let args = args.split_whitespace().collect::<Vec<_>>();
+        matches!(CommandArg::from_yarn(&args), CommandArg::Global(_))
+    }
+
+    #[test]
+    fn parse_global_commands() {
+        for args in [
+            "global add typescript",
+            "--prefix /usr/local global remove typescript",
+            "global upgrade",
+        ] {
+            assert!(is_global(args), "{args}");
+        }
+
+        for args in [
+            "add typescript",
+            "global add",
+            "global list",
+            "--cwd global add typescript",
+        ] {
+            assert!(!is_global(args), "{args}");
+        }
+    }
+}